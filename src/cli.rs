@@ -0,0 +1,14 @@
+//! Command line helpers.
+//! Options are plain `--flag value` pairs read straight from `std::env::args`.
+
+/// Returns the value following `flag` on the command line, if present.
+pub fn arg_value(flag: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != flag);
+    args.next()?;
+    args.next()
+}
+
+/// Returns true if `flag` appears on the command line.
+pub fn has_flag(flag: &str) -> bool {
+    std::env::args().any(|arg| arg == flag)
+}
@@ -0,0 +1,202 @@
+//! Daily Bug — one maze per day, the same for everyone, seeded from the UTC date alone.
+//! `--daily` plays today's maze and `--daily-date YYYY-MM-DD` replays another day's.
+//! The best local finish time for each day is kept in DAILY_RECORDS. Daily runs ignore
+//! the maze generation flags, so every best time was set on the day's real maze.
+
+use bevy::prelude::*;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::cli::{arg_value, has_flag};
+use crate::game_timer::GameTimer;
+use crate::recipe::RECIPE_FLAGS;
+use crate::rng::splitmix64;
+
+/// Where the best time for each day is saved, one `YYYY-MM-DD seconds` line per day.
+pub const DAILY_RECORDS: &str = "daily_best.txt";
+
+/// Seed salt so daily seeds never line up with day numbers typed in as `--seed`.
+const DAILY_SALT: u64 = 0x0044_4149_4C59; // "DAILY"
+
+/// A calendar date in the proleptic Gregorian calendar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Today's date in UTC.
+    pub fn today() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        Self::from_days((secs / 86_400) as i64)
+    }
+
+    /// The date `days` days after 1970-01-01.
+    pub fn from_days(days: i64) -> Self {
+        // Counts in 400-year eras starting on March 1st, so leap days fall at the end of a year.
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_from_march = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+        let month = if month_from_march < 10 { month_from_march + 3 } else { month_from_march - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Date { year: year as i32, month: month as u32, day: day as u32 }
+    }
+
+    /// Days since 1970-01-01; negative before it.
+    pub fn days(self) -> i64 {
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month_from_march = (i64::from(self.month) + 9) % 12;
+        let day_of_year = (153 * month_from_march + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// Parses `YYYY-MM-DD`, rejecting days that do not exist.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.trim().splitn(3, '-');
+        let date = Date {
+            year: parts.next()?.parse().ok()?,
+            month: parts.next()?.parse().ok()?,
+            day: parts.next()?.parse().ok()?,
+        };
+        let valid = (1..=12).contains(&date.month) && date.day >= 1 && Date::from_days(date.days()) == date;
+        valid.then_some(date)
+    }
+
+    /// The run seed for this day's maze. Only the date goes in, so it works offline.
+    pub fn daily_seed(self) -> u64 {
+        // Kept short like time-derived seeds, so it can still be read out and typed back in.
+        splitmix64(self.days() as u64 ^ DAILY_SALT) % 1_000_000_000
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Present while a daily run is being played.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct DailyChallenge {
+    pub date: Date,
+    /// Best finish time for this day on this machine, in seconds.
+    pub best: Option<f32>,
+    /// Finish time of the most recent win this session.
+    pub last: Option<f32>,
+}
+
+impl DailyChallenge {
+    /// A daily run if `--daily` or `--daily-date` was passed, with any saved best time loaded.
+    /// Warns about each generation flag passed alongside, since the daily maze ignores them.
+    pub fn from_args() -> Option<Self> {
+        let date = if let Some(text) = arg_value("--daily-date") {
+            Date::parse(&text).unwrap_or_else(|| {
+                warn!("Invalid daily date '{}', using today", text);
+                Date::today()
+            })
+        } else if has_flag("--daily") {
+            Date::today()
+        } else {
+            return None;
+        };
+        for flag in RECIPE_FLAGS.iter().filter(|flag| has_flag(flag)) {
+            warn!("{} is ignored in daily mode, everyone gets the same maze", flag);
+        }
+        Some(DailyChallenge { date, best: load_records().remove(&date.to_string()), last: None })
+    }
+
+    /// The seed everyone playing this day's maze shares.
+    pub fn seed(&self) -> u64 {
+        self.date.daily_seed()
+    }
+
+    /// Records a finish time, saving it if it beats the day's best. Returns true on a new best.
+    pub fn record(&mut self, seconds: f32) -> bool {
+        self.last = Some(seconds);
+        if self.best.is_some_and(|best| best <= seconds) {
+            return false;
+        }
+        self.best = Some(seconds);
+
+        let mut records = load_records();
+        records.insert(self.date.to_string(), seconds);
+        let text: String = records.iter().map(|(date, secs)| format!("{date} {secs:.2}\n")).collect();
+        if let Err(err) = std::fs::write(DAILY_RECORDS, text) {
+            warn!("Could not save daily best time to {}: {}", DAILY_RECORDS, err);
+        }
+        true
+    }
+}
+
+/// Best time per date from DAILY_RECORDS; empty if the file is missing.
+fn load_records() -> std::collections::BTreeMap<String, f32> {
+    let text = std::fs::read_to_string(DAILY_RECORDS).unwrap_or_default();
+    text.lines()
+        .filter_map(|line| {
+            let (date, secs) = line.split_once(' ')?;
+            Some((date.to_string(), secs.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+/// Records the finish time of a daily run when the player wins.
+pub fn record_daily_time(timer: Res<GameTimer>, daily: Option<ResMut<DailyChallenge>>) {
+    let Some(mut daily) = daily else { return; };
+    let seconds = timer.total - timer.remaining;
+    if daily.record(seconds) {
+        info!("New best for the {} Daily Bug: {:.2}s", daily.date, seconds);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_round_trip_across_centuries() {
+        // From 1600 to 2400, both ways through the epoch and across several 400-year eras.
+        for days in -135_140..157_000 {
+            let date = Date::from_days(days);
+            assert_eq!(date.days(), days, "{}", date);
+            assert_eq!(Date::parse(&date.to_string()), Some(date));
+        }
+    }
+
+    #[test]
+    fn epoch_and_known_days() {
+        assert_eq!(Date::from_days(0), Date { year: 1970, month: 1, day: 1 });
+        assert_eq!(Date::from_days(-1), Date { year: 1969, month: 12, day: 31 });
+        assert_eq!(Date::from_days(10_957), Date { year: 2000, month: 1, day: 1 });
+        assert_eq!(Date { year: 2024, month: 3, day: 1 }.days(), 19_783);
+    }
+
+    #[test]
+    fn leap_days_follow_the_gregorian_rules() {
+        assert!(Date::parse("2000-02-29").is_some());
+        assert!(Date::parse("2024-02-29").is_some());
+        assert!(Date::parse("1900-02-29").is_none());
+        assert!(Date::parse("2023-02-29").is_none());
+        assert!(Date::parse("2100-02-29").is_none());
+        let leap = Date::parse("2000-02-29").unwrap();
+        assert_eq!(Date::from_days(leap.days() + 1), Date { year: 2000, month: 3, day: 1 });
+    }
+
+    #[test]
+    fn parse_rejects_malformed_dates() {
+        for text in ["", "2024", "2024-01", "2024-13-01", "2024-00-10", "2024-04-31", "2024-01-00", "24-1-x"] {
+            assert_eq!(Date::parse(text), None, "{}", text);
+        }
+        assert_eq!(Date::parse(" 2024-1-5 "), Some(Date { year: 2024, month: 1, day: 5 }));
+    }
+}
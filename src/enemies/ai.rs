@@ -1,8 +1,11 @@
 //! Enemy AI — pathfinding and aggro.
-//! Phase 1: stub only. Phase 2: A* on maze graph.
+//! Chasing enemies walk the shared flow field toward the player; one-offs use `EnemyPath`.
 
 use bevy::prelude::*;
 use super::types::Enemy;
+use crate::maze::flow::FlowField;
+use crate::maze::generator::Maze;
+use crate::maze::spatial::{cell_at, cell_center, line_of_sight};
 
 /// Tracks the enemy's current AI state.
 #[derive(Component, Debug, Default, PartialEq)]
@@ -20,9 +23,37 @@ pub struct EnemyPath {
     pub current_waypoint: usize,
 }
 
-/// Checks distance to player and sets aggro state.
-/// Phase 2: replace with A* pathfinding.
+impl EnemyPath {
+    /// Waypoints along the shortest route from `from` to `to`, one per cell center.
+    /// A portal on the route shows up as two waypoints far apart; walking onto the first
+    /// makes the jump, which lands the enemy on the second.
+    pub fn through(maze: &Maze, from: (usize, usize), to: (usize, usize)) -> Option<Self> {
+        let cells = maze.shortest_path(from, to)?;
+        let waypoints = cells.into_iter().map(|(x, y)| cell_center(maze, x, y)).collect();
+        Some(EnemyPath { waypoints, current_waypoint: 0 })
+    }
+}
+
+/// Keeps the `FlowField` resource pointing at the player's cell, rebuilding it when the
+/// player moves to another cell or the walls change. Enemies sample it instead of
+/// searching for a path each.
+pub fn update_flow_field(
+    mut commands: Commands,
+    maze: Res<Maze>,
+    flow: Option<Res<FlowField>>,
+    player_query: Query<&Transform, With<crate::player::controller::Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else { return; };
+    let Some(cell) = cell_at(&maze, player_transform.translation) else { return; };
+    let stale = flow.is_none_or(|flow| flow.target() != cell) || maze.is_changed();
+    if stale {
+        commands.insert_resource(FlowField::toward(&maze, cell));
+    }
+}
+
+/// Sets aggro state: enemies chase a player within range that they can see.
 pub fn update_aggro(
+    maze: Res<Maze>,
     player_query: Query<&Transform, With<crate::player::controller::Player>>,
     mut enemy_query: Query<(&Transform, &Enemy, &mut EnemyAiState)>,
 ) {
@@ -32,10 +63,31 @@ pub fn update_aggro(
         let distance = enemy_transform.translation
             .distance(player_transform.translation);
 
-        *ai_state = if distance <= enemy.aggro_range {
+        let sees_player = distance <= enemy.aggro_range
+            && line_of_sight(&maze, enemy_transform.translation, player_transform.translation);
+        *ai_state = if sees_player {
             EnemyAiState::Chasing
         } else {
             EnemyAiState::Idle
         };
     }
-}
\ No newline at end of file
+}
+
+/// Walks every chasing enemy along the shared flow field toward the player. The field
+/// only crosses sides that can be walked through, so one-way doors hold enemies too.
+pub fn chase_player(
+    time: Res<Time>,
+    maze: Res<Maze>,
+    flow: Option<Res<FlowField>>,
+    mut enemy_query: Query<(&mut Transform, &Enemy, &EnemyAiState)>,
+) {
+    let Some(flow) = flow else { return; };
+    for (mut transform, enemy, ai_state) in enemy_query.iter_mut() {
+        if *ai_state != EnemyAiState::Chasing {
+            continue;
+        }
+        if let Some(direction) = flow.direction(&maze, transform.translation) {
+            transform.translation += direction * enemy.speed * time.delta_secs();
+        }
+    }
+}
@@ -2,4 +2,5 @@
 //! Phase 1: stubs only. Phase 2: full implementation.
 pub mod types;
 pub mod ai;
-pub mod combat;
\ No newline at end of file
+pub mod combat;
+pub mod spawner;
\ No newline at end of file
@@ -0,0 +1,61 @@
+//! Enemy spawner — puts an enemy on every placed spawn cell.
+//! Types are drawn from the enemy stream, so a seed always fields the same enemies.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use super::ai::EnemyAiState;
+use super::combat::EnemyCombatState;
+use super::types::{Enemy, EnemyType};
+use crate::maze::generator::Maze;
+use crate::maze::placement::Placements;
+use crate::maze::spatial::cell_center;
+use crate::rng::{GameRng, Rng, RngStream};
+
+/// Every enemy type, in the order they are drawn.
+const ENEMY_TYPES: [EnemyType; 3] = [EnemyType::GlitchBot, EnemyType::CorruptProcess, EnemyType::VirusSprite];
+
+/// Spawns one capsule enemy per placed enemy cell, idle until it sees the player.
+pub fn spawn_enemies(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    maze: Res<Maze>,
+    placements: Res<Placements>,
+    rng: Res<GameRng>,
+) {
+    // A fresh generator from the stream's seed, so restarts field the same enemies.
+    let mut enemy_rng = Rng::new(rng.stream_seed(RngStream::Enemies));
+    let body = meshes.add(Capsule3d::new(0.3, 0.6));
+
+    for &(x, y) in &placements.enemies {
+        let enemy_type = ENEMY_TYPES[enemy_rng.next_usize(ENEMY_TYPES.len())].clone();
+        let color = match enemy_type {
+            EnemyType::GlitchBot => Color::srgb(0.8, 0.1, 0.1),
+            EnemyType::CorruptProcess => Color::srgb(0.6, 0.1, 0.8),
+            EnemyType::VirusSprite => Color::srgb(0.9, 0.8, 0.1),
+        };
+        commands.spawn((
+            Enemy::new(enemy_type),
+            EnemyAiState::default(),
+            EnemyCombatState::default(),
+            Mesh3d(body.clone()),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: color,
+                emissive: LinearRgba::from(color) * 0.3,
+                ..default()
+            })),
+            Transform::from_translation(cell_center(&maze, x, y) + Vec3::Y * 0.6),
+            Collider::capsule_y(0.3, 0.3),
+            RigidBody::KinematicPositionBased,
+        ));
+    }
+    info!("Spawned {} enemies", placements.enemies.len());
+}
+
+/// Clears away every enemy for a restart, so `spawn_enemies` can put them back on
+/// their spawn cells.
+pub fn despawn_enemies(mut commands: Commands, query: Query<Entity, With<Enemy>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
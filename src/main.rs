@@ -4,8 +4,25 @@
 use bevy::prelude::*;
 use bevy_egui::EguiPlugin;
 use bevy_rapier3d::prelude::*;
-use maze::renderer::spawn_maze;
-use maze::generator::Maze;
+use maze::renderer::{spawn_maze, WallLayout};
+use maze::spatial::cell_center;
+use maze::stairs::use_floor_links;
+use maze::jumps::{spawn_portals, jump_portals, tick_jump_glitch, JumpGlitch};
+use maze::keycards::{spawn_doors_and_keycards, collect_keycards, unlock_doors, clear_keycards, Keyring};
+use maze::glitch::{glitch_walls, restore_maze, GlitchClock, MazeGlitched, StartingMaze};
+use maze::lights::{spawn_lights, flicker_lights};
+use maze::hazards::{track_player_tile, spark_damage, darken_lights, CurrentTile};
+use maze::streaming::{setup_endless, stream_chunks, reward_new_chunks, EndlessMaze};
+use maze::gates::{spawn_gates, clear_gates, pass_one_way_doors, flicker_secret_walls, reveal_secret_walls};
+use maze::generator::{Maze, MAZE_WIDTH, MAZE_HEIGHT};
+use maze::algorithms::MazeAlgorithm;
+use maze::rooms::RoomsAndCorridors;
+use maze::mask::Mask;
+use maze::topology::Topology;
+use maze::stats::{generate_in_band, MazeStats};
+use maze::placement::{Placements, PlacementRule};
+use maze::batches::WALL_BATCH_CELLS;
+use maze::map_image::{MapImage, MapOptions};
 use bevy_egui::egui;
 
 mod states;
@@ -17,9 +34,17 @@ mod combat;
 mod ui;
 mod audio;
 mod game_timer;
+mod cli;
+mod seed;
+mod daily;
+mod rng;
+mod png;
+mod recipe;
 
 // Imports from modules
 use states::GameState;
+use enemies::ai::{update_aggro, update_flow_field, chase_player};
+use enemies::spawner::{spawn_enemies, despawn_enemies};
 use player::controller::{spawn_player, lock_cursor, player_look, player_move};
 use player::stats::regenerate_stamina;
 use player::combat::tick_attack_cooldown;
@@ -33,9 +58,36 @@ use ui::game_over::{render_game_over, render_win};
 use audio::clock::{start_clock_audio, stop_clock_audio};
 use terminal::monitor::spawn_monitor;
 use game_timer::{GameTimer, tick_timer, reset_timer};
+use seed::RunSeed;
+use daily::{record_daily_time, DailyChallenge};
+use rng::{derive_seed, GameRng, Rng, RngStream, RNG_VERSION};
+use cli::{arg_value, has_flag};
+use recipe::MazeRecipe;
 
 fn main() {
-    App::new()
+    if has_flag("--check-golden") {
+        check_golden_mazes();
+    }
+    if has_flag("--bench-flow") {
+        bench_flow();
+    }
+    if has_flag("--bench-walls") {
+        bench_walls();
+    }
+    if let Some(path) = arg_value("--export-map") {
+        export_map(&path);
+    }
+
+    let mut app = App::new();
+    match DailyChallenge::from_args() {
+        Some(daily) => app
+            .insert_resource(RunSeed(daily.seed()))
+            .insert_resource(MazeRecipe::daily())
+            .insert_resource(daily),
+        None => app.insert_resource(RunSeed::from_args()).insert_resource(MazeRecipe::from_args()),
+    };
+
+    app
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 title: "Y2K Scare Maze".into(),
@@ -54,12 +106,17 @@ fn main() {
         .init_resource::<TerminalStyle>()
         .init_resource::<TypewriterState>()
         .init_resource::<GameTimer>()
+        .init_resource::<Keyring>()
+        .init_resource::<GlitchClock>()
+        .init_resource::<CurrentTile>()
+        .init_resource::<JumpGlitch>()
         // Events
         .add_event::<AttackEvent>()
         .add_event::<DamageEvent>()
         .add_event::<DeathEvent>()
+        .add_event::<MazeGlitched>()
         // Startup systems
-        .insert_resource(Maze::generate(12345))
+        .add_systems(PreStartup, init_maze)
         .add_systems(Update, (
             player_look,
             player_move,
@@ -67,8 +124,24 @@ fn main() {
             tick_attack_cooldown,
             render_hud,
             interact_terminal,
+            tick_jump_glitch,
             tick_timer,
         ).run_if(in_state(GameState::Exploring)))
+        // Systems that find cells with `cell_at` only know the fixed maze; endless mode
+        // has no `Maze` beyond its starting chunk, so they sit it out.
+        .add_systems(Update, (
+            track_player_tile.before(player_move),
+            use_floor_links,
+            jump_portals,
+            collect_keycards,
+            unlock_doors,
+            pass_one_way_doors,
+            flicker_secret_walls,
+            reveal_secret_walls,
+            glitch_walls,
+            spark_damage,
+            darken_lights,
+        ).run_if(in_state(GameState::Exploring).and(not(resource_exists::<EndlessMaze>))))
         // AtTerminal state systems
         .add_systems(Update, (
             tick_typewriter,
@@ -80,14 +153,35 @@ fn main() {
             resolve_attack,
             handle_death,
         ).run_if(in_state(GameState::Combat)))
+        // Enemies that see the player chase them along one shared flow field
+        .add_systems(Update, (
+            update_aggro,
+            update_flow_field,
+            chase_player.after(update_aggro).after(update_flow_field),
+        ).run_if(in_state(GameState::Exploring).and(not(resource_exists::<EndlessMaze>))))
+        // Ceiling lights stutter harder as the clock runs down
+        .add_systems(Update, flicker_lights.run_if(in_state(GameState::Exploring)))
+        // Endless mode streams chunks in place of the fixed maze
+        .add_systems(Update, (
+            stream_chunks,
+            reward_new_chunks,
+        ).run_if(in_state(GameState::Exploring).and(resource_exists::<EndlessMaze>)))
         .add_systems(Startup, (
             spawn_player, 
             lock_cursor, 
-            spawn_maze, 
-            spawn_terminal, 
-            spawn_monitor, 
             start_clock_audio,
             load_fonts))
+        .add_systems(Startup, (
+            spawn_maze,
+            spawn_doors_and_keycards,
+            spawn_gates,
+            spawn_portals,
+            spawn_lights,
+            spawn_enemies,
+            spawn_terminal,
+            spawn_monitor,
+        ).run_if(not(resource_exists::<EndlessMaze>)))
+        .add_systems(Startup, setup_endless.run_if(resource_exists::<EndlessMaze>))
         // GameOver state systems
         .add_systems(Update,
             render_game_over.run_if(in_state(GameState::GameOver))
@@ -96,16 +190,329 @@ fn main() {
         .add_systems(Update,
             render_win.run_if(in_state(GameState::Win))
         )
-        .add_systems(OnEnter(GameState::Win), 
-            stop_clock_audio
-        )
+        .add_systems(OnEnter(GameState::Win), (
+            stop_clock_audio,
+            record_daily_time,
+        ))
         .add_systems(OnEnter(GameState::GameOver), 
             stop_clock_audio
         )
         .add_systems(OnEnter(GameState::Exploring), 
         reset_timer
-    )
-        .run();
+    );
+
+    // Restarting from the game over or win screen plays the same maze again from scratch
+    for screen in [GameState::GameOver, GameState::Win] {
+        app.add_systems(OnExit(screen), (
+            (
+                restore_maze,
+                clear_keycards,
+                spawn_doors_and_keycards,
+                clear_gates,
+                spawn_gates,
+                despawn_enemies,
+                spawn_enemies,
+            ).chain().run_if(resource_exists::<StartingMaze>),
+            reset_game,
+        ).chain());
+    }
+    app.run();
+}
+
+/// Regenerates the pinned mazes in GOLDEN_DIR and exits non-zero if any changed.
+fn check_golden_mazes() -> ! {
+    match maze::golden::check_golden(std::path::Path::new(maze::golden::GOLDEN_DIR)) {
+        Ok(count) => {
+            println!("{} golden mazes match (rng v{})", count, RNG_VERSION);
+            std::process::exit(0);
+        }
+        Err(failures) => {
+            for failure in &failures {
+                eprintln!("golden mismatch: {}", failure);
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Times one flow field per target against one search per agent, for a crowd of
+/// `--bench-flow <agents>` (default BENCH_AGENTS) chasing the player along the route
+/// to the terminal in the run's maze, then exits.
+fn bench_flow() -> ! {
+    const BENCH_AGENTS: usize = 50;
+    let agents = arg_value("--bench-flow")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(BENCH_AGENTS);
+    let seed = RunSeed::from_args();
+    let rng = GameRng::new(seed.0);
+    let maze_seed = rng.stream_seed(RngStream::Maze);
+    let (maze, placements) = build_maze(seed.0, maze_seed, &MazeRecipe::from_args());
+
+    let mut agent_rng = Rng::new(rng.stream_seed(RngStream::Enemies));
+    let crowd = maze.place_random(&[PlacementRule::Avoid(vec![placements.player])], agents, &mut agent_rng);
+    let route = maze.shortest_path(placements.player, placements.terminal).unwrap_or_default();
+    let result = maze::flow::benchmark(&maze, &crowd, &route);
+
+    let per_target = |total: std::time::Duration| total.as_secs_f64() * 1e6 / result.targets.max(1) as f64;
+    println!(
+        "{}x{} maze, {} agents, {} player cells (seed {})",
+        maze.width(), maze.height(), result.agents, result.targets, seed.0,
+    );
+    println!("  flow field:      {:>10.1} us per player cell", per_target(result.flow_field));
+    println!("  per-agent BFS:   {:>10.1} us per player cell", per_target(result.per_agent));
+    println!(
+        "  speedup:         {:>10.1}x",
+        result.per_agent.as_secs_f64() / result.flow_field.as_secs_f64().max(f64::EPSILON),
+    );
+    std::process::exit(0);
+}
+
+/// Spawns the run's maze twice without opening a window, once with every wall on its own
+/// and once merged into batches of WALL_BATCH_CELLS square, then prints the entities with
+/// a mesh, the meshes and the colliders each way side by side and exits.
+fn bench_walls() -> ! {
+    let seed = RunSeed::from_args();
+    let rng = GameRng::new(seed.0);
+    let (maze, placements) = build_maze(seed.0, rng.stream_seed(RngStream::Maze), &MazeRecipe::from_args());
+    let stats = maze.wall_batch_stats();
+
+    let spawn = |layout: WallLayout| {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .insert_resource(maze.clone())
+            .insert_resource(placements.clone())
+            .insert_resource(rng.clone())
+            .insert_resource(layout)
+            .add_systems(Startup, (
+                spawn_maze,
+                spawn_doors_and_keycards,
+                spawn_gates,
+                spawn_portals,
+                spawn_lights,
+                spawn_enemies,
+            ));
+        app.update();
+        let world = app.world_mut();
+        let drawn = world.query_filtered::<(), With<Mesh3d>>().iter(world).count();
+        let colliders = world.query_filtered::<(), With<Collider>>().iter(world).count();
+        (drawn, world.resource::<Assets<Mesh>>().len(), colliders)
+    };
+    let single = spawn(WallLayout::Single);
+    let batched = spawn(WallLayout::Batched);
+
+    println!(
+        "{}x{} maze, {} walls in {} batches of {}x{} cells (seed {})",
+        maze.width(), maze.height(), stats.walls, stats.batches, WALL_BATCH_CELLS, WALL_BATCH_CELLS, seed.0,
+    );
+    println!("                   single   batched");
+    println!("  mesh entities: {:>8} {:>9}", single.0, batched.0);
+    println!("  meshes:        {:>8} {:>9}", single.1, batched.1);
+    println!("  colliders:     {:>8} {:>9}", single.2, batched.2);
+    std::process::exit(0);
+}
+
+/// Builds the run's maze without opening a window and writes its map to `path`, as PNG
+/// if the name ends in `.png` and SVG otherwise. `--map-solution` adds the route to the
+/// terminal and `--map-enemies` the enemy spawns.
+fn export_map(path: &str) -> ! {
+    let (seed, recipe) = match DailyChallenge::from_args() {
+        Some(daily) => (RunSeed(daily.seed()), MazeRecipe::daily()),
+        None => (RunSeed::from_args(), MazeRecipe::from_args()),
+    };
+    let rng = GameRng::new(seed.0);
+    let (maze, placements) = build_maze(seed.0, rng.stream_seed(RngStream::Maze), &recipe);
+    let options = MapOptions {
+        solution: has_flag("--map-solution"),
+        enemies: has_flag("--map-enemies"),
+        ..default()
+    };
+    let map = MapImage::new(&maze, &placements, options);
+    let bytes = if path.ends_with(".png") { map.to_png() } else { map.to_svg().into_bytes() };
+    match std::fs::write(path, bytes) {
+        Ok(()) => {
+            println!("Wrote {}x{} map of seed {} to {}", map.width(), map.height(), seed.0, path);
+            std::process::exit(0);
+        }
+        Err(err) => {
+            eprintln!("Could not write map {}: {}", path, err);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Seeds the run's RNG streams and builds the maze before anything spawns into it.
+/// With `--endless` the maze is streamed in chunks instead, and the `Maze` resource
+/// holds just the starting chunk, to place the player in.
+fn init_maze(mut commands: Commands, seed: Res<RunSeed>, recipe: Res<MazeRecipe>) {
+    let rng = GameRng::new(seed.0);
+    let (maze, placements) = if recipe.endless {
+        let endless = EndlessMaze::new(rng.stream_seed(RngStream::Maze), recipe.algorithm);
+        let start = endless.chunk((0, 0));
+        let placements = Placements::plan(&start, endless.seed);
+        info!("Run seed {} (rng v{}): endless mode", seed.0, RNG_VERSION);
+        commands.insert_resource(endless);
+        (start, placements)
+    } else {
+        let (maze, placements) = build_maze(seed.0, rng.stream_seed(RngStream::Maze), &recipe);
+        commands.insert_resource(StartingMaze(maze.clone()));
+        (maze, placements)
+    };
+    commands.insert_resource(maze);
+    commands.insert_resource(placements);
+    commands.insert_resource(rng);
+}
+
+/// Generates the run's maze from the seed and the recipe, read from these command line
+/// options unless this is a daily run:
+/// `--algorithm <name>` picks the carver, `--braid <fraction>` removes dead ends,
+/// `--floors <n>` stacks that many floors joined by stairs and lifts,
+/// `--rooms <n>` carves up to n office rooms joined by corridors,
+/// `--portals <n>` joins up to n pairs of far-apart cells with network jumps,
+/// `--mask <path|circle>` carves a single floor inside a shape instead of a rectangle,
+/// `--topology <square|hex|polar>` picks square cells, hexagons, or concentric rings,
+/// `--difficulty <easy|normal|hard>` rerolls the seed until the layout scores in that band,
+/// `--doors <n>` locks up to n doors on the way to the terminal, each with a keycard,
+/// `--one-way <n>` turns up to n passages into one-way doors that never trap the player,
+/// `--secret-walls <n>` hides up to n passages behind walls that can be walked through,
+/// `--hazards <fraction>` turns that share of cells into sparking, flooded, dark or static tiles,
+/// `--maze-file <path>` loads a hand-authored text maze instead, and
+/// `--print-maze` writes the result to stdout for bug reports, doors, portals, hazards and
+/// all, in a form `--maze-file` loads back unchanged.
+fn build_maze(run_seed: u64, maze_seed: u64, recipe: &MazeRecipe) -> (Maze, Placements) {
+    let mut maze = recipe.maze_file.as_deref()
+        .and_then(load_maze_file)
+        .unwrap_or_else(|| generate_maze(maze_seed, recipe));
+    let placements = Placements::plan(&maze, maze_seed);
+
+    let doors = recipe.doors;
+    if doors > 0 {
+        // Doors and keycards draw from their own sub-stream, like braiding.
+        let mut rng = Rng::new(derive_seed(maze_seed, 1));
+        let locked = maze.lock_doors(doors, placements.player, placements.terminal, &mut rng);
+        if locked < doors {
+            warn!("Only found room for {} of {} locked doors", locked, doors);
+        }
+    }
+
+    // One-way passages go in after the doors so their checks can see the locks.
+    let one_way = recipe.one_way;
+    if one_way > 0 {
+        let mut rng = Rng::new(derive_seed(maze_seed, 3));
+        let placed = maze.add_one_way_passages(one_way, placements.player, placements.terminal, &mut rng);
+        if placed < one_way {
+            warn!("Only found room for {} of {} one-way doors", placed, one_way);
+        }
+    }
+
+    let secret_walls = recipe.secret_walls;
+    if secret_walls > 0 {
+        let mut rng = Rng::new(derive_seed(maze_seed, 4));
+        let hidden = maze.add_secret_walls(secret_walls, &mut rng);
+        info!("Hid {} secret walls", hidden);
+    }
+
+    let hazards = recipe.hazards;
+    if hazards > 0.0 {
+        // Hazards get a sub-stream of their own too.
+        let mut rng = Rng::new(derive_seed(maze_seed, 2));
+        let count = maze.sprinkle_hazards(hazards, &[placements.player, placements.terminal], &mut rng);
+        info!("Sprinkled {} hazard tiles", count);
+    }
+
+    let stats = MazeStats::analyze(&maze, placements.player, placements.terminal);
+    info!(
+        "Run seed {} (rng v{}): difficulty {:.1}, solution {} steps, {} dead ends",
+        run_seed, RNG_VERSION, stats.difficulty(), stats.solution_length, stats.dead_ends,
+    );
+    if has_flag("--print-maze") {
+        if maze.topology() == Topology::Square {
+            println!("# seed {} (rng v{})\n{}", run_seed, RNG_VERSION, maze);
+        } else {
+            warn!("The text format only holds square mazes, not printing this one");
+        }
+    }
+    (maze, placements)
+}
+
+/// Carves a fresh maze with the recipe's algorithm, room, floor, braid, and difficulty options.
+fn generate_maze(seed: u64, recipe: &MazeRecipe) -> Maze {
+    let algorithm = recipe.algorithm;
+    let braid = recipe.braid;
+    let portals = recipe.portals;
+    let topology = recipe.topology;
+
+    let mut rooms = recipe.rooms;
+    if rooms > 0 && topology != Topology::Square {
+        warn!("Rooms need square cells, ignoring --rooms");
+        rooms = 0;
+    }
+    let rooms_and_corridors = RoomsAndCorridors { corridors: algorithm, rooms };
+    let algorithm: &dyn MazeAlgorithm = if rooms > 0 { &rooms_and_corridors } else { &algorithm };
+
+    let floors = recipe.floors;
+    let mask = recipe.mask.as_deref().and_then(load_mask);
+    if mask.is_some() && floors > 1 {
+        warn!("Masked mazes have a single floor, ignoring --floors");
+    }
+    if mask.is_some() && topology != Topology::Square {
+        warn!("Masks are drawn in square cells, ignoring --topology");
+    }
+
+    let generate = |seed: u64| {
+        let mut maze = match &mask {
+            Some(mask) => Maze::generate_masked(mask, seed, algorithm),
+            None => Maze::generate_floors(MAZE_WIDTH, MAZE_HEIGHT, floors, topology, seed, algorithm),
+        };
+        // Braiding draws from its own sub-stream so it never shifts the carving.
+        maze.braid(braid, &mut Rng::new(derive_seed(seed, 0)));
+        // Portals go in before anything is placed, so placement and scoring see the jumps.
+        if portals > 0 {
+            let placed = maze.place_portals(portals, &mut Rng::new(derive_seed(seed, 5)));
+            if placed < portals {
+                warn!("Only found room for {} of {} portals", placed, portals);
+            }
+        }
+        maze
+    };
+
+    let Some(difficulty) = recipe.difficulty else {
+        return generate(seed);
+    };
+    // Score each attempt between the cells the run will actually use.
+    generate_in_band(seed, difficulty.band(), generate, |maze| {
+        let placements = Placements::plan(maze, seed);
+        MazeStats::analyze(maze, placements.player, placements.terminal).difficulty()
+    })
+}
+
+/// Loads a mask file, or builds a circle the width of a default maze for `circle`.
+/// Logs and returns None if the mask cannot be used.
+fn load_mask(spec: &str) -> Option<Mask> {
+    if spec == "circle" {
+        return Some(Mask::circle(MAZE_WIDTH));
+    }
+    let text = std::fs::read_to_string(spec)
+        .map_err(|err| warn!("Could not read mask {}: {}", spec, err))
+        .ok()?;
+    Mask::parse(&text)
+        .map_err(|err| warn!("Could not use mask {}: {}", spec, err))
+        .ok()
+}
+
+/// Reads a text maze from disk, logging and returning None if it cannot be used.
+fn load_maze_file(path: &str) -> Option<Maze> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| warn!("Could not read maze file {}: {}", path, err))
+        .ok()?;
+    let maze = Maze::from_ascii(&text)
+        .map_err(|err| warn!("Could not parse maze file {}: {}", path, err))
+        .ok()?;
+    if !maze.is_connected() {
+        warn!("Maze file {} has cells unreachable from the start", path);
+    }
+    Some(maze)
 }
 
 // Resets the game state to the initial conditions for a new playthrough.
@@ -113,9 +520,12 @@ fn reset_game(
     mut player_query: Query<&mut Transform, With<player::controller::Player>>,
     mut puzzle: ResMut<terminal::puzzle::TerminalPuzzle>,
     mut tw: ResMut<terminal::ui::TypewriterState>,
+    maze: Res<Maze>,
+    placements: Res<Placements>,
 ) {
     if let Ok(mut transform) = player_query.get_single_mut() {
-        transform.translation = Vec3::new(1.0, 0.5, 1.0);
+        let (x, y) = placements.player;
+        transform.translation = cell_center(&maze, x, y) + Vec3::Y * 0.5;
     }
     *puzzle = terminal::puzzle::TerminalPuzzle::default();
     tw.reset();
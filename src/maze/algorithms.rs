@@ -0,0 +1,493 @@
+//! Maze carving algorithms behind a common trait.
+//! Each one turns a fully walled grid into a perfect maze, but with a different texture:
+//! backtracker and hunt-and-kill give long rivers, Prim's and Kruskal's give short
+//! branchy dead ends, Wilson's is unbiased, binary tree and Eller's carve row by row.
+
+use crate::maze::generator::{Maze, NORTH, SOUTH, EAST, WEST, NORTH_WEST, SOUTH_EAST};
+use crate::maze::topology::Topology;
+use crate::rng::Rng;
+
+/// A strategy for carving passages into a fully walled maze.
+pub trait MazeAlgorithm {
+    /// Opens passages until every cell is reachable from every other cell.
+    fn carve(&self, maze: &mut Maze, rng: &mut Rng);
+}
+
+/// Depth-first search with backtracking. Long, winding corridors.
+pub struct RecursiveBacktracker;
+
+/// Randomized Prim's: grows the maze from a frontier of adjacent cells.
+pub struct Prim;
+
+/// Randomized Kruskal's: merges random wall-separated regions with union-find.
+pub struct Kruskal;
+
+/// Wilson's: loop-erased random walks, every spanning tree equally likely.
+pub struct Wilson;
+
+/// Eller's: carves one row at a time while tracking connected sets.
+pub struct Eller;
+
+/// Binary tree: every cell opens north or west, biased toward the top-left.
+pub struct BinaryTree;
+
+/// Hunt-and-kill: random walk until stuck, then hunt for a cell next to the maze.
+pub struct HuntAndKill;
+
+/// Every built-in algorithm, selectable per run by name.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MazeAlgorithmKind {
+    #[default]
+    RecursiveBacktracker,
+    Prim,
+    Kruskal,
+    Wilson,
+    Eller,
+    BinaryTree,
+    HuntAndKill,
+}
+
+impl MazeAlgorithmKind {
+    /// All built-in algorithms in declaration order.
+    pub const ALL: [MazeAlgorithmKind; 7] = [
+        MazeAlgorithmKind::RecursiveBacktracker,
+        MazeAlgorithmKind::Prim,
+        MazeAlgorithmKind::Kruskal,
+        MazeAlgorithmKind::Wilson,
+        MazeAlgorithmKind::Eller,
+        MazeAlgorithmKind::BinaryTree,
+        MazeAlgorithmKind::HuntAndKill,
+    ];
+
+    /// Short lowercase name used on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            MazeAlgorithmKind::RecursiveBacktracker => "backtracker",
+            MazeAlgorithmKind::Prim => "prim",
+            MazeAlgorithmKind::Kruskal => "kruskal",
+            MazeAlgorithmKind::Wilson => "wilson",
+            MazeAlgorithmKind::Eller => "eller",
+            MazeAlgorithmKind::BinaryTree => "binary-tree",
+            MazeAlgorithmKind::HuntAndKill => "hunt-and-kill",
+        }
+    }
+
+    /// Looks up an algorithm by its `name`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+impl MazeAlgorithm for MazeAlgorithmKind {
+    fn carve(&self, maze: &mut Maze, rng: &mut Rng) {
+        match self {
+            MazeAlgorithmKind::RecursiveBacktracker => RecursiveBacktracker.carve(maze, rng),
+            MazeAlgorithmKind::Prim => Prim.carve(maze, rng),
+            MazeAlgorithmKind::Kruskal => Kruskal.carve(maze, rng),
+            MazeAlgorithmKind::Wilson => Wilson.carve(maze, rng),
+            MazeAlgorithmKind::Eller => Eller.carve(maze, rng),
+            MazeAlgorithmKind::BinaryTree => BinaryTree.carve(maze, rng),
+            MazeAlgorithmKind::HuntAndKill => HuntAndKill.carve(maze, rng),
+        }
+    }
+}
+
+impl MazeAlgorithm for RecursiveBacktracker {
+    fn carve(&self, maze: &mut Maze, rng: &mut Rng) {
+        let mut visited = vec![false; maze.width() * maze.height()];
+        let mut stack: Vec<(usize, usize)> = Vec::new();
+
+        let start = maze.first_cell();
+        visited[maze.index(start.0, start.1)] = true;
+        stack.push(start);
+
+        while let Some(&(x, y)) = stack.last() {
+            let neighbors = unvisited_neighbors(maze, x, y, &visited);
+            if neighbors.is_empty() {
+                stack.pop();
+            } else {
+                let idx = rng.next_usize(neighbors.len());
+                let (nx, ny, dir) = neighbors[idx];
+                maze.carve(x, y, dir);
+                visited[maze.index(nx, ny)] = true;
+                stack.push((nx, ny));
+            }
+        }
+    }
+}
+
+impl MazeAlgorithm for Prim {
+    fn carve(&self, maze: &mut Maze, rng: &mut Rng) {
+        let mut in_maze = vec![false; maze.width() * maze.height()];
+        let mut frontier: Vec<(usize, usize)> = Vec::new();
+
+        let start = random_cell(maze, rng);
+        add_to_maze(maze, start, &mut in_maze, &mut frontier);
+
+        while !frontier.is_empty() {
+            let (x, y) = frontier.swap_remove(rng.next_usize(frontier.len()));
+            let inside: Vec<_> = maze
+                .neighbors(x, y)
+                .into_iter()
+                .filter(|&(nx, ny, _)| in_maze[maze.index(nx, ny)])
+                .collect();
+            let (_, _, dir) = inside[rng.next_usize(inside.len())];
+            maze.carve(x, y, dir);
+            add_to_maze(maze, (x, y), &mut in_maze, &mut frontier);
+        }
+    }
+}
+
+/// Marks a cell as part of the maze and pushes its new frontier neighbors.
+fn add_to_maze(
+    maze: &Maze,
+    (x, y): (usize, usize),
+    in_maze: &mut [bool],
+    frontier: &mut Vec<(usize, usize)>,
+) {
+    in_maze[maze.index(x, y)] = true;
+    for (nx, ny, _) in maze.neighbors(x, y) {
+        if !in_maze[maze.index(nx, ny)] && !frontier.contains(&(nx, ny)) {
+            frontier.push((nx, ny));
+        }
+    }
+}
+
+impl MazeAlgorithm for Kruskal {
+    fn carve(&self, maze: &mut Maze, rng: &mut Rng) {
+        let mut edges: Vec<(usize, usize, u16)> = Vec::new();
+        for y in 0..maze.height() {
+            for x in 0..maze.width() {
+                for &dir in maze.topology().forward_directions() {
+                    if maze.step(x, y, dir).is_some() { edges.push((x, y, dir)); }
+                }
+            }
+        }
+        rng.shuffle(&mut edges);
+
+        let mut sets = DisjointSet::new(maze.width() * maze.height());
+        for (x, y, dir) in edges {
+            let (nx, ny) = maze.step(x, y, dir).expect("edge stays inside the grid");
+            if sets.union(maze.index(x, y), maze.index(nx, ny)) {
+                maze.carve(x, y, dir);
+            }
+        }
+    }
+}
+
+/// Union-find over cell indices, used by Kruskal's algorithm and room stitching.
+pub(crate) struct DisjointSet {
+    parent: Vec<usize>,
+}
+
+impl DisjointSet {
+    /// Creates `len` singleton sets.
+    pub(crate) fn new(len: usize) -> Self {
+        DisjointSet { parent: (0..len).collect() }
+    }
+
+    /// Returns the representative of the set containing `i`.
+    pub(crate) fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    /// Merges the sets containing `a` and `b`. Returns false if they were already joined.
+    pub(crate) fn union(&mut self, a: usize, b: usize) -> bool {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra == rb {
+            return false;
+        }
+        self.parent[rb] = ra;
+        true
+    }
+}
+
+impl MazeAlgorithm for Wilson {
+    fn carve(&self, maze: &mut Maze, rng: &mut Rng) {
+        let total = maze.width() * maze.height();
+        let mut in_maze = vec![false; total];
+        // Direction last taken out of each cell during the current walk.
+        let mut exit_dir = vec![0u16; total];
+
+        // Void cells count as done so no walk starts there; walks never step into them.
+        for (idx, done) in in_maze.iter_mut().enumerate() {
+            *done = !maze.in_mask(idx % maze.width(), idx / maze.width());
+        }
+        loop {
+            let first = rng.next_usize(total);
+            if !in_maze[first] {
+                in_maze[first] = true;
+                break;
+            }
+        }
+
+        for start in 0..total {
+            if in_maze[start] {
+                continue;
+            }
+            let start = (start % maze.width(), start / maze.width());
+
+            // Random walk until the maze is hit; overwriting exits erases loops.
+            let (mut x, mut y) = start;
+            while !in_maze[maze.index(x, y)] {
+                let neighbors = maze.neighbors(x, y);
+                let (nx, ny, dir) = neighbors[rng.next_usize(neighbors.len())];
+                exit_dir[maze.index(x, y)] = dir;
+                (x, y) = (nx, ny);
+            }
+
+            // Replay the loop-erased path and carve it.
+            let (mut x, mut y) = start;
+            while !in_maze[maze.index(x, y)] {
+                let dir = exit_dir[maze.index(x, y)];
+                in_maze[maze.index(x, y)] = true;
+                maze.carve(x, y, dir);
+                (x, y) = maze.step(x, y, dir).expect("walk stays inside the grid");
+            }
+        }
+    }
+}
+
+impl MazeAlgorithm for Eller {
+    fn carve(&self, maze: &mut Maze, rng: &mut Rng) {
+        let width = maze.width();
+        let height = maze.height();
+        // Set id of each cell in the current row; 0 means "not assigned yet".
+        let mut row_sets = vec![0usize; width];
+        let mut next_set = 1;
+        // Hex rows have no straight south side, so drop through the south-east one.
+        let down = if maze.topology() == Topology::Hex { SOUTH_EAST } else { SOUTH };
+
+        for y in 0..height {
+            let last_row = y + 1 == height;
+
+            for set in row_sets.iter_mut() {
+                if *set == 0 {
+                    *set = next_set;
+                    next_set += 1;
+                }
+            }
+
+            // Join horizontal neighbors from different sets. The last row must join them all.
+            // Sets are still merged across void so the row logic holds; the gaps left
+            // behind are stitched up after carving.
+            for x in 0..width - 1 {
+                let (left, right) = (row_sets[x], row_sets[x + 1]);
+                if left != right && (last_row || rng.next_bool()) {
+                    if maze.step(x, y, EAST).is_some() {
+                        maze.carve(x, y, EAST);
+                    }
+                    for set in row_sets.iter_mut() {
+                        if *set == right {
+                            *set = left;
+                        }
+                    }
+                }
+            }
+
+            if last_row {
+                break;
+            }
+
+            // Every set must drop at least one passage into the next row.
+            let mut next_row = vec![0usize; width];
+            let mut ids: Vec<usize> = row_sets.clone();
+            ids.sort_unstable();
+            ids.dedup();
+            for id in ids {
+                let mut members: Vec<usize> = (0..width).filter(|&x| row_sets[x] == id).collect();
+                rng.shuffle(&mut members);
+                let drops = 1 + rng.next_usize(members.len());
+                for &x in &members[..drops] {
+                    if let Some((nx, _)) = maze.step(x, y, down) {
+                        maze.carve(x, y, down);
+                        next_row[nx] = id;
+                    }
+                }
+            }
+            row_sets = next_row;
+        }
+    }
+}
+
+impl MazeAlgorithm for BinaryTree {
+    fn carve(&self, maze: &mut Maze, rng: &mut Rng) {
+        // Hex rows have no straight north side, so lean north-west instead.
+        let up = if maze.topology() == Topology::Hex { NORTH_WEST } else { NORTH };
+        for y in 0..maze.height() {
+            for x in 0..maze.width() {
+                let north = maze.step(x, y, up).is_some();
+                // Never wrap west around a polar ring, or the inner ring closes into a loop.
+                let west = x > 0 && maze.step(x, y, WEST).is_some();
+                let dir = match (north, west) {
+                    (true, true) => if rng.next_bool() { up } else { WEST },
+                    (true, false) => up,
+                    (false, true) => WEST,
+                    (false, false) => continue,
+                };
+                maze.carve(x, y, dir);
+            }
+        }
+    }
+}
+
+impl MazeAlgorithm for HuntAndKill {
+    fn carve(&self, maze: &mut Maze, rng: &mut Rng) {
+        let mut visited = vec![false; maze.width() * maze.height()];
+        let start = random_cell(maze, rng);
+        visited[maze.index(start.0, start.1)] = true;
+        let mut current = Some(start);
+
+        while let Some((x, y)) = current {
+            // Kill: walk randomly through unvisited cells.
+            let neighbors = unvisited_neighbors(maze, x, y, &visited);
+            if !neighbors.is_empty() {
+                let (nx, ny, dir) = neighbors[rng.next_usize(neighbors.len())];
+                maze.carve(x, y, dir);
+                visited[maze.index(nx, ny)] = true;
+                current = Some((nx, ny));
+                continue;
+            }
+
+            // Hunt: scan for an unvisited cell bordering the maze and connect it.
+            current = None;
+            'hunt: for hy in 0..maze.height() {
+                for hx in 0..maze.width() {
+                    if visited[maze.index(hx, hy)] {
+                        continue;
+                    }
+                    let inside: Vec<_> = maze
+                        .neighbors(hx, hy)
+                        .into_iter()
+                        .filter(|&(nx, ny, _)| visited[maze.index(nx, ny)])
+                        .collect();
+                    if inside.is_empty() {
+                        continue;
+                    }
+                    let (_, _, dir) = inside[rng.next_usize(inside.len())];
+                    maze.carve(hx, hy, dir);
+                    visited[maze.index(hx, hy)] = true;
+                    current = Some((hx, hy));
+                    break 'hunt;
+                }
+            }
+        }
+    }
+}
+
+/// Joins disconnected regions by opening random walls between them, Kruskal-style,
+/// opening only the walls needed to keep the maze connected. Used to give rooms their
+/// doorways and to stitch masked mazes back together.
+pub(crate) fn reconnect(maze: &mut Maze, rng: &mut Rng) {
+    let mut sets = DisjointSet::new(maze.width() * maze.height());
+    let mut walls: Vec<(usize, usize, u16)> = Vec::new();
+
+    for y in 0..maze.height() {
+        for x in 0..maze.width() {
+            for &dir in maze.topology().forward_directions() {
+                let Some((nx, ny)) = maze.step(x, y, dir) else { continue };
+                if maze.cell(x, y).is_open(dir) {
+                    sets.union(maze.index(x, y), maze.index(nx, ny));
+                } else {
+                    walls.push((x, y, dir));
+                }
+            }
+        }
+    }
+
+    rng.shuffle(&mut walls);
+    for (x, y, dir) in walls {
+        let (nx, ny) = maze.step(x, y, dir).expect("wall stays inside the grid");
+        if sets.union(maze.index(x, y), maze.index(nx, ny)) {
+            maze.carve(x, y, dir);
+        }
+    }
+}
+
+/// Picks a random cell inside the mask, redrawing if it lands on void.
+fn random_cell(maze: &Maze, rng: &mut Rng) -> (usize, usize) {
+    loop {
+        let (x, y) = (rng.next_usize(maze.width()), rng.next_usize(maze.height()));
+        if maze.in_mask(x, y) {
+            return (x, y);
+        }
+    }
+}
+
+/// Returns all unvisited neighbors of (x, y) with the direction leading to them.
+fn unvisited_neighbors(
+    maze: &Maze,
+    x: usize,
+    y: usize,
+    visited: &[bool],
+) -> Vec<(usize, usize, u16)> {
+    maze.neighbors(x, y)
+        .into_iter()
+        .filter(|&(nx, ny, _)| !visited[maze.index(nx, ny)])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::mask::Mask;
+
+    /// Open passages, each counted once from the cell on either side of it.
+    fn passage_count(maze: &Maze) -> usize {
+        let mut open_sides = 0;
+        for y in 0..maze.height() {
+            for x in 0..maze.width() {
+                open_sides += maze.cell(x, y).passages.count_ones() as usize;
+            }
+        }
+        open_sides / 2
+    }
+
+    fn assert_perfect(maze: &Maze, case: &str) {
+        assert!(maze.is_connected(), "{case}: unreachable cells");
+        assert_eq!(passage_count(maze), maze.cell_count() - 1, "{case}: not a spanning tree");
+    }
+
+    #[test]
+    fn every_cell_reachable_from_the_corner() {
+        let sizes = [(1, 1), (1, 9), (9, 1), (2, 2), (5, 8), (20, 20), (31, 17)];
+        for algorithm in MazeAlgorithmKind::ALL {
+            for (width, height) in sizes {
+                for seed in [0, 1, 42, 0xDEAD_BEEF] {
+                    let maze = Maze::generate_with(width, height, seed, &algorithm);
+                    assert_perfect(&maze, &format!("{} {width}x{height} seed {seed}", algorithm.name()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn masked_grids_stay_perfect() {
+        let ring = Mask::from_ascii("#######\n#.....#\n#.###.#\n#.#.#.#\n###.###\n").unwrap();
+        let masks = [Mask::circle(11), Mask::circle(4), ring];
+        for algorithm in MazeAlgorithmKind::ALL {
+            for (index, mask) in masks.iter().enumerate() {
+                for seed in [3, 99] {
+                    let maze = Maze::generate_masked(mask, seed, &algorithm);
+                    assert_perfect(&maze, &format!("{} mask {index} seed {seed}", algorithm.name()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn other_topologies_stay_perfect() {
+        for algorithm in MazeAlgorithmKind::ALL {
+            for topology in Topology::ALL {
+                for seed in [5, 77] {
+                    let maze = Maze::generate_shaped(9, 7, topology, seed, &algorithm);
+                    assert_perfect(&maze, &format!("{} {topology:?} seed {seed}", algorithm.name()));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Wall batches — drawn walls grouped into square blocks of cells, one per floor,
+//! so each block can be built as a single mesh and a single collider.
+//! Building the batches in play lives in `renderer`.
+
+use std::collections::BTreeMap;
+use crate::maze::generator::Maze;
+
+/// Cells along each side of the block of cells one wall batch covers.
+pub const WALL_BATCH_CELLS: usize = 8;
+
+/// Which batch a wall belongs to: its floor, then the block's column and row on that floor.
+pub type WallBatchKey = (usize, usize, usize);
+
+/// How many walls a maze draws and how many batches they fall into, each batch
+/// spawned as one entity with one mesh and one collider.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WallBatchStats {
+    pub walls: usize,
+    pub batches: usize,
+}
+
+impl Maze {
+    /// Whether the `direction` side of (x, y) gets a wall: a closed forward side, or any
+    /// closed side with nothing beyond it (the outer border, the polar hub, or void outside
+    /// a mask). One-way doors and secret walls are left out; `gates` draws those.
+    pub fn draws_wall(&self, x: usize, y: usize, direction: u16) -> bool {
+        if !self.in_mask(x, y) || self.cell(x, y).is_open(direction) || self.is_one_way(x, y, direction) {
+            return false;
+        }
+        self.topology().forward_directions().contains(&direction) || self.step(x, y, direction).is_none()
+    }
+
+    /// The batch that draws the walls of (x, y).
+    pub fn wall_batch_of(&self, x: usize, y: usize) -> WallBatchKey {
+        let floor = self.floor_of(y);
+        let row = y - floor * self.rows_per_floor();
+        (floor, x / WALL_BATCH_CELLS, row / WALL_BATCH_CELLS)
+    }
+
+    /// Every drawn wall, as (x, y, direction), in the batch at `key`.
+    pub fn batch_walls(&self, key: WallBatchKey) -> Vec<(usize, usize, u16)> {
+        let (floor, column, row) = key;
+        let top = floor * self.rows_per_floor() + row * WALL_BATCH_CELLS;
+        let bottom = (top + WALL_BATCH_CELLS).min((floor + 1) * self.rows_per_floor());
+        let left = column * WALL_BATCH_CELLS;
+        let right = (left + WALL_BATCH_CELLS).min(self.width());
+        let mut walls = Vec::new();
+        for y in top..bottom {
+            for x in left..right {
+                for &direction in self.directions() {
+                    if self.draws_wall(x, y, direction) {
+                        walls.push((x, y, direction));
+                    }
+                }
+            }
+        }
+        walls
+    }
+
+    /// Every drawn wall, grouped by batch. Batches with no walls are left out.
+    pub fn wall_batches(&self) -> BTreeMap<WallBatchKey, Vec<(usize, usize, u16)>> {
+        let mut batches: BTreeMap<WallBatchKey, Vec<(usize, usize, u16)>> = BTreeMap::new();
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                for &direction in self.directions() {
+                    if self.draws_wall(x, y, direction) {
+                        batches.entry(self.wall_batch_of(x, y)).or_default().push((x, y, direction));
+                    }
+                }
+            }
+        }
+        batches
+    }
+
+    /// Counts the drawn walls and the batches they fall into.
+    pub fn wall_batch_stats(&self) -> WallBatchStats {
+        let batches = self.wall_batches();
+        WallBatchStats {
+            walls: batches.values().map(Vec::len).sum(),
+            batches: batches.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::algorithms::MazeAlgorithmKind;
+    use crate::maze::topology::Topology;
+
+    #[test]
+    fn stats_count_every_drawn_wall_once() {
+        for topology in Topology::ALL {
+            let maze = Maze::generate_floors(19, 11, 2, topology, 5, &MazeAlgorithmKind::default());
+            let batches = maze.wall_batches();
+            let mut drawn = 0;
+            for y in 0..maze.height() {
+                for x in 0..maze.width() {
+                    drawn += maze.directions().iter().filter(|&&d| maze.draws_wall(x, y, d)).count();
+                }
+            }
+            for (&key, walls) in &batches {
+                assert_eq!(&maze.batch_walls(key), walls, "{topology:?} {key:?}");
+            }
+            assert_eq!(maze.wall_batch_stats(), WallBatchStats { walls: drawn, batches: batches.len() });
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! Braiding — turns a perfect maze into one with loops.
+//! Dead ends are opened into a neighbor so chases have escape routes.
+
+use crate::maze::generator::Maze;
+use crate::rng::Rng;
+
+impl Maze {
+    /// Removes roughly `fraction` (0.0..=1.0) of the dead ends by carving one extra passage
+    /// out of each. Neighbors that are dead ends themselves are preferred, so a single
+    /// carve can fix two at once. Passages stay symmetric between neighbors.
+    pub fn braid(&mut self, fraction: f32, rng: &mut Rng) {
+        let fraction = fraction.clamp(0.0, 1.0);
+        let mut dead_ends = self.dead_ends();
+        rng.shuffle(&mut dead_ends);
+
+        for (x, y) in dead_ends {
+            // An earlier carve may already have fixed this one.
+            if self.cell(x, y).passages.count_ones() != 1 {
+                continue;
+            }
+            if rng.next_f32() >= fraction {
+                continue;
+            }
+
+            let closed: Vec<_> = self
+                .neighbors(x, y)
+                .into_iter()
+                .filter(|&(_, _, dir)| !self.cell(x, y).is_open(dir))
+                .collect();
+            let dead_neighbors: Vec<_> = closed
+                .iter()
+                .copied()
+                .filter(|&(nx, ny, _)| self.cell(nx, ny).passages.count_ones() == 1)
+                .collect();
+            let candidates = if dead_neighbors.is_empty() { closed } else { dead_neighbors };
+
+            if candidates.is_empty() {
+                continue;
+            }
+            let (_, _, dir) = candidates[rng.next_usize(candidates.len())];
+            self.carve(x, y, dir);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::algorithms::MazeAlgorithmKind;
+
+    #[test]
+    fn braiding_removes_its_share_of_dead_ends() {
+        let carve = || Maze::generate_with(20, 20, 3, &MazeAlgorithmKind::default());
+        let before = carve().dead_ends().len();
+        for (fraction, low, high) in [(0.0, 1.0, 1.0), (0.5, 0.3, 0.6), (1.0, 0.0, 0.0)] {
+            let mut maze = carve();
+            maze.braid(fraction, &mut Rng::new(1));
+            let left = maze.dead_ends().len() as f32 / before as f32;
+            assert!((low..=high).contains(&left), "braid {fraction} left {left} of the dead ends");
+            assert!(maze.is_connected(), "braid {fraction}");
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Endless mazes — an unbounded plane of square chunks, each a small maze of its own.
+//! Every chunk and every border between two chunks is seeded from its coordinates alone,
+//! so chunks can be built in any order and always meet up with the same openings.
+//! Streaming chunks in and out around the player lives in `streaming`.
+
+use bevy::prelude::*;
+use crate::maze::algorithms::MazeAlgorithm;
+use crate::maze::generator::{Maze, EAST, NORTH, SOUTH, WEST};
+use crate::maze::spatial::CELL_SIZE;
+use crate::rng::{derive_seed, splitmix64, Rng};
+
+/// Cells along each side of a chunk.
+pub const CHUNK_SIZE: usize = 10;
+
+/// Most openings in the border between two chunks; there is always at least one.
+pub const MAX_BORDER_OPENINGS: usize = 3;
+
+/// Seed salts for chunk interiors and chunk borders.
+const CHUNK_SALT: u64 = 0x0043_4855_4E4B; // "CHUNK"
+const BORDER_SALT: u64 = 0x0042_4F52_4452; // "BORDR"
+
+/// Chunk coordinates: chunk (0, 0) starts at the world origin, x runs east and y south.
+pub type ChunkCoord = (i32, i32);
+
+/// Packs chunk coordinates into one value to seed from.
+fn pack(coord: ChunkCoord) -> u64 {
+    ((coord.0 as u32 as u64) << 32) | coord.1 as u32 as u64
+}
+
+/// Seed for the interior of the chunk at `coord`.
+pub fn chunk_seed(world_seed: u64, coord: ChunkCoord) -> u64 {
+    derive_seed(world_seed ^ CHUNK_SALT, pack(coord))
+}
+
+/// Offsets along the `side` border of chunk `coord` where a passage crosses into the
+/// neighboring chunk, sorted. A border is seeded from the chunk north or west of it, so
+/// both chunks that share it agree on the openings.
+pub fn border_openings(world_seed: u64, coord: ChunkCoord, side: u16) -> Vec<usize> {
+    let (owner, vertical) = match side {
+        EAST => (coord, true),
+        SOUTH => (coord, false),
+        WEST => ((coord.0 - 1, coord.1), true),
+        NORTH => ((coord.0, coord.1 - 1), false),
+        _ => panic!("chunks only have four sides, not {side:#06b}"),
+    };
+    let mut rng = Rng::new(derive_seed(world_seed ^ BORDER_SALT, splitmix64(pack(owner)) ^ vertical as u64));
+    let mut offsets: Vec<usize> = (0..CHUNK_SIZE).collect();
+    rng.shuffle(&mut offsets);
+    offsets.truncate(1 + rng.next_usize(MAX_BORDER_OPENINGS));
+    offsets.sort_unstable();
+    offsets
+}
+
+/// World position of the north-west corner of chunk `coord`.
+pub fn chunk_origin(coord: ChunkCoord) -> Vec3 {
+    let size = CHUNK_SIZE as f32 * CELL_SIZE;
+    Vec3::new(coord.0 as f32 * size, 0.0, coord.1 as f32 * size)
+}
+
+/// The chunk whose floor contains `position`.
+pub fn chunk_at(position: Vec3) -> ChunkCoord {
+    let size = CHUNK_SIZE as f32 * CELL_SIZE;
+    ((position.x / size).floor() as i32, (position.z / size).floor() as i32)
+}
+
+impl Maze {
+    /// Generates the chunk at `coord` with `algorithm`: a CHUNK_SIZE square maze whose
+    /// border cells also have their sides open wherever a passage leads into the next
+    /// chunk. Those sides have no cell beyond them inside this maze, so they never show
+    /// up among `open_neighbors`; they only tell the renderer where to leave gaps.
+    pub fn generate_chunk(world_seed: u64, coord: ChunkCoord, algorithm: &dyn MazeAlgorithm) -> Self {
+        let mut maze = Maze::generate_with(CHUNK_SIZE, CHUNK_SIZE, chunk_seed(world_seed, coord), algorithm);
+        let last = CHUNK_SIZE - 1;
+        for side in [NORTH, SOUTH, EAST, WEST] {
+            for offset in border_openings(world_seed, coord, side) {
+                let (x, y) = match side {
+                    NORTH => (offset, 0),
+                    SOUTH => (offset, last),
+                    EAST => (last, offset),
+                    _ => (0, offset),
+                };
+                maze.cell_mut(x, y).passages |= side;
+            }
+        }
+        maze
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::algorithms::MazeAlgorithmKind;
+
+    #[test]
+    fn chunks_depend_only_on_seed_and_coordinates() {
+        for algorithm in MazeAlgorithmKind::ALL {
+            for coord in [(0, 0), (3, -2), (-7, 5)] {
+                let chunk = Maze::generate_chunk(42, coord, &algorithm);
+                assert_eq!(chunk, Maze::generate_chunk(42, coord, &algorithm), "{algorithm:?} {coord:?}");
+                assert!(chunk.is_connected(), "{algorithm:?} {coord:?}");
+            }
+        }
+        let algorithm = MazeAlgorithmKind::default();
+        assert_ne!(Maze::generate_chunk(42, (1, 1), &algorithm), Maze::generate_chunk(43, (1, 1), &algorithm));
+        assert_ne!(Maze::generate_chunk(42, (1, 1), &algorithm), Maze::generate_chunk(42, (1, 2), &algorithm));
+    }
+
+    #[test]
+    fn neighboring_chunks_open_the_same_border_cells() {
+        let algorithm = MazeAlgorithmKind::default();
+        let last = CHUNK_SIZE - 1;
+        for cy in -2..=2 {
+            for cx in -2..=2 {
+                let chunk = Maze::generate_chunk(7, (cx, cy), &algorithm);
+                let east = Maze::generate_chunk(7, (cx + 1, cy), &algorithm);
+                let south = Maze::generate_chunk(7, (cx, cy + 1), &algorithm);
+                let mut crossings = (0, 0);
+                for offset in 0..CHUNK_SIZE {
+                    let across = chunk.cell(last, offset).is_open(EAST);
+                    assert_eq!(across, east.cell(0, offset).is_open(WEST), "({cx}, {cy}) east {offset}");
+                    let down = chunk.cell(offset, last).is_open(SOUTH);
+                    assert_eq!(down, south.cell(offset, 0).is_open(NORTH), "({cx}, {cy}) south {offset}");
+                    crossings.0 += across as usize;
+                    crossings.1 += down as usize;
+                }
+                assert!((1..=MAX_BORDER_OPENINGS).contains(&crossings.0), "({cx}, {cy}) east");
+                assert!((1..=MAX_BORDER_OPENINGS).contains(&crossings.1), "({cx}, {cy}) south");
+            }
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! Locked doors and keycards — color-coded locks on passages, with each keycard
+//! placed where it can be reached before its door, so no seed can lock a run out.
+//! Picking keycards up and opening doors in play lives in `keycards`.
+
+use crate::maze::generator::Maze;
+use crate::rng::Rng;
+
+/// Color of a keycard and the doors it opens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeyColor {
+    Red,
+    Green,
+    Blue,
+    Yellow,
+}
+
+impl KeyColor {
+    /// Every color, in the order doors are handed them.
+    pub const ALL: [KeyColor; 4] = [KeyColor::Red, KeyColor::Green, KeyColor::Blue, KeyColor::Yellow];
+
+    /// The name used in text mazes.
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyColor::Red => "red",
+            KeyColor::Green => "green",
+            KeyColor::Blue => "blue",
+            KeyColor::Yellow => "yellow",
+        }
+    }
+
+    /// Looks a color up by its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|color| color.name() == name)
+    }
+
+    /// Display color as 8-bit sRGB, shared by the meshes and the HUD.
+    pub fn rgb(self) -> [u8; 3] {
+        match self {
+            KeyColor::Red => [220, 40, 40],
+            KeyColor::Green => [40, 200, 70],
+            KeyColor::Blue => [50, 110, 240],
+            KeyColor::Yellow => [240, 200, 40],
+        }
+    }
+}
+
+/// A locked door across the passage leaving (x, y) in `direction`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Door {
+    pub x: usize,
+    pub y: usize,
+    pub direction: u16,
+    pub color: KeyColor,
+}
+
+impl Door {
+    /// The cell on the far side of the door.
+    pub fn other_side(&self, maze: &Maze) -> (usize, usize) {
+        maze.step(self.x, self.y, self.direction)
+            .expect("door leads to a cell inside the grid")
+    }
+
+    /// Returns true if the door stands between cells `a` and `b`, in either order.
+    pub fn between(&self, maze: &Maze, a: (usize, usize), b: (usize, usize)) -> bool {
+        let ends = ((self.x, self.y), self.other_side(maze));
+        ends == (a, b) || ends == (b, a)
+    }
+}
+
+/// A keycard lying in cell (x, y) that opens every door of its color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Keycard {
+    pub x: usize,
+    pub y: usize,
+    pub color: KeyColor,
+}
+
+impl Maze {
+    /// The door between two neighboring cells, if there is one.
+    pub fn door_between(&self, a: (usize, usize), b: (usize, usize)) -> Option<&Door> {
+        self.doors().iter().find(|door| door.between(self, a, b))
+    }
+
+    /// Takes away every door of `color`, once it has been opened in play.
+    pub fn unlock(&mut self, color: KeyColor) {
+        self.doors_mut().retain(|door| door.color != color);
+    }
+
+    /// Flood fill from `from` through open passages, only passing doors whose color
+    /// is in `held`. Indexed like the cell storage.
+    pub fn reachable_with_keys(&self, from: (usize, usize), held: &[KeyColor]) -> Vec<bool> {
+        let mut reached = vec![false; self.width() * self.height()];
+        let mut stack = vec![from];
+        reached[self.index(from.0, from.1)] = true;
+
+        while let Some((x, y)) = stack.pop() {
+            for (nx, ny) in self.open_neighbors(x, y) {
+                let idx = self.index(nx, ny);
+                if reached[idx] {
+                    continue;
+                }
+                if let Some(door) = self.door_between((x, y), (nx, ny)) {
+                    if !held.contains(&door.color) {
+                        continue;
+                    }
+                }
+                reached[idx] = true;
+                stack.push((nx, ny));
+            }
+        }
+        reached
+    }
+
+    /// Plays the locks out from `start` already holding `held`: pick up every reachable
+    /// keycard, open every door those keys fit, and repeat until nothing changes.
+    /// Returns the cells reached, indexed like the cell storage.
+    fn collect_keys(&self, start: (usize, usize), held: &[KeyColor]) -> Vec<bool> {
+        let mut held = held.to_vec();
+        loop {
+            let reached = self.reachable_with_keys(start, &held);
+            let new_keys: Vec<KeyColor> = self
+                .keycards()
+                .iter()
+                .filter(|key| reached[self.index(key.x, key.y)] && !held.contains(&key.color))
+                .map(|key| key.color)
+                .collect();
+            if new_keys.is_empty() {
+                return reached;
+            }
+            held.extend(new_keys);
+        }
+    }
+
+    /// Returns true if a player starting at `start` can collect every keycard and
+    /// reach `goal` without ever needing a key that is behind its own door.
+    pub fn locks_solvable(&self, start: (usize, usize), goal: (usize, usize)) -> bool {
+        let reached = self.collect_keys(start, &[]);
+        reached[self.index(goal.0, goal.1)]
+            && self.keycards().iter().all(|key| reached[self.index(key.x, key.y)])
+    }
+
+    /// Returns true if a player at `start` holding `held` can still get to `goal`,
+    /// picking up whatever keycards they pass on the way.
+    pub fn can_reach(&self, start: (usize, usize), goal: (usize, usize), held: &[KeyColor]) -> bool {
+        self.collect_keys(start, held)[self.index(goal.0, goal.1)]
+    }
+
+    /// Locks up to `count` doors along the route from `start` to `goal`, spaced out
+    /// along it, and drops each door's keycard somewhere reachable before it
+    /// (preferring dead ends). Every door is checked with `locks_solvable` before it
+    /// is kept. In a braided maze a loop may lead around a door.
+    /// Returns how many doors were placed.
+    pub fn lock_doors(
+        &mut self,
+        count: usize,
+        start: (usize, usize),
+        goal: (usize, usize),
+        rng: &mut Rng,
+    ) -> usize {
+        let count = count.min(KeyColor::ALL.len());
+        let Some(path) = self.shortest_path(start, goal) else { return 0 };
+
+        // Planar steps along the route; stairs, lifts and the open floor of a room cannot
+        // hold a door, though a room's doorway can.
+        let steps: Vec<(usize, usize, u16)> = path
+            .windows(2)
+            .filter(|pair| {
+                let ((x, y), (nx, ny)) = (pair[0], pair[1]);
+                !self.room_at(x, y).is_some_and(|room| room.contains(nx, ny))
+            })
+            .filter_map(|pair| {
+                let ((x, y), next) = (pair[0], pair[1]);
+                self.directions()
+                    .iter()
+                    .copied()
+                    .find(|&dir| self.step(x, y, dir) == Some(next))
+                    .map(|dir| (x, y, dir))
+            })
+            .collect();
+
+        let mut placed = 0;
+        let mut next_step = 0;
+        for (i, color) in KeyColor::ALL.into_iter().take(count).enumerate() {
+            // Aim for evenly spaced doors, sliding further along if a spot does not work out.
+            let target = (steps.len() * (i + 1) / (count + 1)).max(next_step);
+            let mut locked = false;
+
+            for (step, &(x, y, direction)) in steps.iter().enumerate().skip(target) {
+                self.doors_mut().push(Door { x, y, direction, color });
+                if let Some(key) = self.place_keycard(start, goal, color, rng) {
+                    self.keycards_mut().push(key);
+                    if self.locks_solvable(start, goal) {
+                        next_step = step + 1;
+                        locked = true;
+                        break;
+                    }
+                    self.keycards_mut().pop();
+                }
+                self.doors_mut().pop();
+            }
+
+            if !locked {
+                break;
+            }
+            placed += 1;
+        }
+
+        debug_assert!(self.locks_solvable(start, goal), "locked doors made the maze unsolvable");
+        placed
+    }
+
+    /// Picks a free cell for a `color` keycard among the cells reachable with the keys
+    /// placed so far, or None if there is nowhere to put it.
+    fn place_keycard(
+        &self,
+        start: (usize, usize),
+        goal: (usize, usize),
+        color: KeyColor,
+        rng: &mut Rng,
+    ) -> Option<Keycard> {
+        let reached = self.collect_keys(start, &[]);
+        let free: Vec<(usize, usize)> = (0..self.height())
+            .flat_map(|y| (0..self.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| reached[self.index(x, y)])
+            .filter(|&cell| cell != start && cell != goal)
+            .filter(|&(x, y)| !self.keycards().iter().any(|key| (key.x, key.y) == (x, y)))
+            .collect();
+
+        let dead_ends: Vec<(usize, usize)> = free
+            .iter()
+            .copied()
+            .filter(|&(x, y)| self.open_neighbors(x, y).len() == 1)
+            .collect();
+        let candidates = if dead_ends.is_empty() { free } else { dead_ends };
+
+        let (x, y) = *candidates.get(rng.next_usize(candidates.len().max(1)))?;
+        Some(Keycard { x, y, color })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::algorithms::MazeAlgorithmKind;
+    use crate::maze::topology::Topology;
+
+    /// Locks a maze between its corner and the farthest cell from it, and checks that
+    /// every keycard can be picked up without its own key and the goal stays solvable.
+    fn assert_keys_before_doors(mut maze: Maze, seed: u64) {
+        let start = (0, 0);
+        let (goal, _) = maze.distances(start).farthest();
+        let placed = maze.lock_doors(4, start, goal, &mut Rng::new(seed));
+        assert!(placed > 0, "seed {seed}: no doors placed");
+        assert_eq!(maze.doors().len(), placed);
+        assert_eq!(maze.keycards().len(), placed);
+        for key in maze.keycards() {
+            let others: Vec<KeyColor> = KeyColor::ALL.into_iter().filter(|&color| color != key.color).collect();
+            let reached = maze.reachable_with_keys(start, &others);
+            assert!(reached[maze.index(key.x, key.y)], "seed {seed}: {:?} key is behind its door", key.color);
+        }
+        assert!(maze.locks_solvable(start, goal), "seed {seed}");
+        assert!(!maze.reachable_with_keys(start, &[])[maze.index(goal.0, goal.1)], "seed {seed}: doors bar nothing");
+    }
+
+    #[test]
+    fn every_keycard_lies_before_its_door() {
+        for seed in [1, 9, 77, 2024, 0xBEEF] {
+            assert_keys_before_doors(Maze::generate_with(16, 12, seed, &MazeAlgorithmKind::default()), seed);
+            let mut braided = Maze::generate_with(16, 12, seed, &MazeAlgorithmKind::Prim);
+            braided.braid(0.3, &mut Rng::new(seed));
+            let start = (0, 0);
+            let (goal, _) = braided.distances(start).farthest();
+            braided.lock_doors(4, start, goal, &mut Rng::new(seed));
+            assert!(braided.locks_solvable(start, goal), "braided seed {seed}");
+        }
+    }
+
+    #[test]
+    fn other_topologies_keep_keys_before_doors() {
+        for topology in Topology::ALL {
+            for seed in [3, 40, 500] {
+                assert_keys_before_doors(Maze::generate_shaped(12, 9, topology, seed, &MazeAlgorithmKind::default()), seed);
+            }
+        }
+    }
+
+    #[test]
+    fn unlocking_opens_the_way_for_everyone() {
+        let mut maze = Maze::generate_with(10, 10, 5, &MazeAlgorithmKind::default());
+        let start = (0, 0);
+        let (goal, _) = maze.distances(start).farthest();
+        maze.lock_doors(1, start, goal, &mut Rng::new(5));
+        let door = maze.doors()[0];
+        assert!(!maze.reachable_with_keys(start, &[])[maze.index(goal.0, goal.1)]);
+        maze.unlock(door.color);
+        assert!(maze.doors().is_empty());
+        assert!(maze.reachable_with_keys(start, &[])[maze.index(goal.0, goal.1)]);
+    }
+}
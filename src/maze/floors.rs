@@ -0,0 +1,123 @@
+//! Multi-floor mazes — independent floors joined by stairwells and lifts.
+//! Each floor is carved on its own, then every pair of neighboring floors gets
+//! at least one vertical link so the whole building stays connected.
+
+use crate::maze::algorithms::MazeAlgorithm;
+use crate::maze::generator::{Maze, UP, DOWN};
+use crate::maze::rooms::Room;
+use crate::maze::topology::Topology;
+use crate::rng::{derive_seed, Rng};
+
+/// How a vertical link looks and feels in game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FloorLinkKind {
+    Stairs,
+    Lift,
+}
+
+/// A stairwell or lift between (x, y) and the cell directly below it on the next floor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FloorLink {
+    pub x: usize,
+    /// Row of the upper cell, in stacked maze coordinates.
+    pub y: usize,
+    pub kind: FloorLinkKind,
+}
+
+impl FloorLink {
+    /// The cell this link arrives at on the floor below.
+    pub fn lower(&self, maze: &Maze) -> (usize, usize) {
+        (self.x, self.y + maze.rows_per_floor())
+    }
+}
+
+/// Seed salt for link placement, kept clear of the per-floor salts.
+const LINK_SALT: u64 = u64::MAX;
+
+/// Roughly one extra link per this many cells of floor area.
+const CELLS_PER_LINK: usize = 150;
+
+impl Maze {
+    /// Opens a stairwell or lift from (x, y) down to the same spot on the next floor.
+    pub fn link_floors(&mut self, x: usize, y: usize, kind: FloorLinkKind) {
+        self.carve(x, y, DOWN);
+        self.links_mut().push(FloorLink { x, y, kind });
+    }
+
+    /// Generates `floors` floors of `width` x `rows_per_floor` laid out as `topology`, each
+    /// carved with `algorithm`, then links every floor to the one below it. Floor 0 uses
+    /// `seed` unchanged, so a one-floor building matches `Maze::generate_shaped`.
+    pub fn generate_floors(
+        width: usize,
+        rows_per_floor: usize,
+        floors: usize,
+        topology: Topology,
+        seed: u64,
+        algorithm: &dyn MazeAlgorithm,
+    ) -> Self {
+        let mut maze = Maze::new_shaped(width, rows_per_floor, floors, topology);
+
+        for floor in 0..floors {
+            let floor_seed = if floor == 0 { seed } else { derive_seed(seed, floor as u64) };
+            let layer = Maze::generate_shaped(width, rows_per_floor, topology, floor_seed, algorithm);
+            for row in 0..rows_per_floor {
+                for x in 0..width {
+                    maze.cell_mut(x, floor * rows_per_floor + row).passages = layer.cell(x, row).passages;
+                }
+            }
+            for room in layer.rooms() {
+                let y = room.y + floor * rows_per_floor;
+                maze.rooms_mut().push(Room { y, ..*room });
+            }
+        }
+
+        let mut rng = Rng::new(derive_seed(seed, LINK_SALT));
+        let links_per_floor = 1 + (width * rows_per_floor) / CELLS_PER_LINK;
+        for floor in 0..floors.saturating_sub(1) {
+            let mut placed = 0;
+            while placed < links_per_floor {
+                let x = rng.next_usize(width);
+                let y = floor * rows_per_floor + rng.next_usize(rows_per_floor);
+                // Keep the start cell free and never stack two links in one cell.
+                let taken = (x, y) == (0, 0)
+                    || maze.cell(x, y).is_open(DOWN)
+                    || maze.cell(x, y).is_open(UP);
+                if taken && width * rows_per_floor > links_per_floor + 1 {
+                    continue;
+                }
+                let kind = if rng.next_bool() { FloorLinkKind::Stairs } else { FloorLinkKind::Lift };
+                maze.link_floors(x, y, kind);
+                placed += 1;
+            }
+        }
+        // Kept in reading order, the order the text format lists them in.
+        maze.links_mut().sort_by_key(|link| (link.y, link.x));
+
+        debug_assert!(maze.is_connected(), "floors were left disconnected");
+        maze
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::algorithms::MazeAlgorithmKind;
+
+    #[test]
+    fn every_floor_links_down_to_the_next() {
+        let (width, rows, floors) = (9, 7, 4);
+        for seed in [21, 22] {
+            let maze = Maze::generate_floors(width, rows, floors, Topology::Square, seed, &MazeAlgorithmKind::default());
+            for floor in 0..floors - 1 {
+                let links: Vec<_> = maze.links().iter().filter(|link| maze.floor_of(link.y) == floor).collect();
+                assert!(!links.is_empty(), "seed {seed}: no way down from floor {floor}");
+                for link in links {
+                    let (x, y) = link.lower(&maze);
+                    assert_eq!(maze.floor_of(y), floor + 1);
+                    assert!(maze.cell(link.x, link.y).is_open(DOWN) && maze.cell(x, y).is_open(UP));
+                }
+            }
+            assert_eq!(maze.distances((0, 0)).reachable_count(), width * rows * floors, "seed {seed}");
+        }
+    }
+}
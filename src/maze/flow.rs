@@ -0,0 +1,168 @@
+//! Flow fields — one breadth-first sweep out from a target tells every cell which way
+//! to go, so a crowd of enemies shares a single search instead of running one each.
+//! Sweeps follow moves backwards, so one-way passages, stairs and portals route right.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use bevy::prelude::*;
+use crate::maze::generator::Maze;
+use crate::maze::spatial::{cell_at, cell_center};
+
+/// The next step toward one target cell from every cell that can reach it.
+#[derive(Resource, Clone, Debug)]
+pub struct FlowField {
+    width: usize,
+    target: (usize, usize),
+    next: Vec<Option<usize>>,
+    distances: Vec<Option<usize>>,
+}
+
+impl FlowField {
+    /// Sweeps `maze` backwards from `target`.
+    pub fn toward(maze: &Maze, target: (usize, usize)) -> Self {
+        let total = maze.width() * maze.height();
+
+        // Who can step into each cell; differs from who it can step to wherever
+        // a passage only runs one way.
+        let mut arrivals: Vec<Vec<usize>> = vec![Vec::new(); total];
+        for y in 0..maze.height() {
+            for x in 0..maze.width() {
+                for (nx, ny) in maze.open_neighbors(x, y) {
+                    arrivals[maze.index(nx, ny)].push(maze.index(x, y));
+                }
+            }
+        }
+
+        let mut next = vec![None; total];
+        let mut distances = vec![None; total];
+        let mut queue = VecDeque::new();
+        let start = maze.index(target.0, target.1);
+        distances[start] = Some(0);
+        queue.push_back(start);
+
+        while let Some(idx) = queue.pop_front() {
+            let next_distance = distances[idx].map(|d: usize| d + 1);
+            for &from in &arrivals[idx] {
+                if distances[from].is_none() {
+                    distances[from] = next_distance;
+                    next[from] = Some(idx);
+                    queue.push_back(from);
+                }
+            }
+        }
+
+        FlowField { width: maze.width(), target, next, distances }
+    }
+
+    /// The cell the field leads to.
+    pub fn target(&self) -> (usize, usize) {
+        self.target
+    }
+
+    /// The cell to move into from (x, y), or None at the target or where it cannot be reached.
+    pub fn next_cell(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        self.next[y * self.width + x].map(|idx| (idx % self.width, idx / self.width))
+    }
+
+    /// Steps from (x, y) to the target, or None if it cannot be reached.
+    pub fn distance(&self, x: usize, y: usize) -> Option<usize> {
+        self.distances[y * self.width + x]
+    }
+
+    /// Which way to head across the floor from `position`, as a flat unit vector.
+    /// Stairs, lifts and portals are taken from the middle of their cell, so when the
+    /// next step is one of those this points at the middle of the current cell instead.
+    /// None if `position` is off the maze, at the target, or cut off from it.
+    pub fn direction(&self, maze: &Maze, position: Vec3) -> Option<Vec3> {
+        let (x, y) = cell_at(maze, position)?;
+        let (nx, ny) = self.next_cell(x, y)?;
+        let beside = maze.neighbors(x, y).iter().any(|&(cx, cy, _)| (cx, cy) == (nx, ny));
+        let aim = if beside { cell_center(maze, nx, ny) } else { cell_center(maze, x, y) };
+        let offset = aim - position;
+        Vec3::new(offset.x, 0.0, offset.z).try_normalize()
+    }
+}
+
+/// How long it took to route a crowd, with one flow field against one search per agent.
+#[derive(Clone, Copy, Debug)]
+pub struct FlowBenchmark {
+    pub agents: usize,
+    pub targets: usize,
+    pub flow_field: Duration,
+    pub per_agent: Duration,
+}
+
+/// Routes every agent one step toward each target in turn, as a crowd chasing a player
+/// along `targets` would, first with a flow field per target and then with a shortest
+/// path search per agent per target.
+pub fn benchmark(maze: &Maze, agents: &[(usize, usize)], targets: &[(usize, usize)]) -> FlowBenchmark {
+    let mut flow_steps = Vec::with_capacity(agents.len() * targets.len());
+    let started = Instant::now();
+    for &target in targets {
+        let field = FlowField::toward(maze, target);
+        flow_steps.extend(agents.iter().map(|&(x, y)| field.next_cell(x, y)));
+    }
+    let flow_field = started.elapsed();
+
+    let mut search_steps = Vec::with_capacity(agents.len() * targets.len());
+    let started = Instant::now();
+    for &target in targets {
+        search_steps.extend(
+            agents.iter().map(|&agent| maze.shortest_path(agent, target).and_then(|path| path.get(1).copied())),
+        );
+    }
+    let per_agent = started.elapsed();
+
+    // Keep the steps alive so neither loop is optimised away.
+    std::hint::black_box((flow_steps, search_steps));
+
+    FlowBenchmark { agents: agents.len(), targets: targets.len(), flow_field, per_agent }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::algorithms::MazeAlgorithmKind;
+    use crate::maze::topology::Topology;
+    use crate::rng::Rng;
+
+    /// Checks every cell's step against a fresh search. Ties can pick different cells,
+    /// but never a longer way round, and never a side that cannot be walked through.
+    fn assert_matches_search(maze: &Maze, target: (usize, usize)) {
+        let field = FlowField::toward(maze, target);
+        for y in 0..maze.height() {
+            for x in 0..maze.width() {
+                let search = maze.shortest_path((x, y), target);
+                assert_eq!(field.distance(x, y), search.as_ref().map(|path| path.len() - 1), "({x}, {y})");
+                let Some((nx, ny)) = field.next_cell(x, y) else {
+                    assert!(search.is_none_or(|path| path.len() == 1), "({x}, {y}) has no step");
+                    continue;
+                };
+                assert!(maze.open_neighbors(x, y).contains(&(nx, ny)), "({x}, {y}) steps through a wall");
+                assert_eq!(field.distance(nx, ny).map(|d| d + 1), field.distance(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn steps_match_a_search_from_every_cell() {
+        for seed in [1, 42, 2024] {
+            let mut maze = Maze::generate_floors(12, 9, 2, Topology::Square, seed, &MazeAlgorithmKind::Prim);
+            let mut rng = Rng::new(seed);
+            let goal = (maze.width() - 1, maze.height() - 1);
+            maze.braid(0.4, &mut rng);
+            maze.place_portals(2, &mut rng);
+            maze.add_one_way_passages(6, (0, 0), goal, &mut rng);
+            for target in [(0, 0), goal, (5, 4)] {
+                assert_matches_search(&maze, target);
+            }
+        }
+    }
+
+    #[test]
+    fn benchmark_routes_every_agent_once_per_target() {
+        let maze = Maze::generate_with(10, 10, 7, &MazeAlgorithmKind::default());
+        let result = benchmark(&maze, &[(0, 0), (9, 9), (3, 6)], &[(5, 5), (5, 6)]);
+        assert_eq!((result.agents, result.targets), (3, 2));
+    }
+}
@@ -0,0 +1,221 @@
+//! One-way doors and secret walls in play — a one-way door gives way once the player
+//! stands on its entry side and stays open until they are clear of it; a secret wall
+//! flickers faintly, lets the player walk through, and vanishes for good when E is
+//! pressed next to it.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use crate::maze::generator::Maze;
+use crate::maze::renderer::{wall_placement, WallSlot, WALL_COLOR, WALL_EMISSIVE, WALL_HEIGHT, WALL_THICKNESS};
+use crate::maze::spatial::{cell_at, cell_center};
+use crate::player::controller::{Player, PLAYER_RADIUS};
+
+/// How close to a secret wall the player must stand to open it with E.
+pub const REVEAL_RANGE: f32 = 1.2;
+
+/// How far the glow of a secret wall wanders from a real wall's, as a fraction.
+pub const FLICKER_DEPTH: f32 = 0.15;
+
+/// A door that can only be walked through from the `entry` cell. Only the player
+/// collides with it; enemies move along the maze's passages, which already run one
+/// way through it.
+#[derive(Component, Debug)]
+pub struct OneWayDoor {
+    pub entry: (usize, usize),
+    /// Width of the panel, along the wall.
+    pub length: f32,
+}
+
+impl OneWayDoor {
+    /// Returns true if a player at `position` would still touch the panel standing at `transform`.
+    pub fn touches(&self, transform: &Transform, position: Vec3) -> bool {
+        let local = transform.rotation.inverse() * (position - transform.translation);
+        local.x.abs() < self.length / 2.0 + PLAYER_RADIUS
+            && local.z.abs() < WALL_THICKNESS / 2.0 + PLAYER_RADIUS
+            && local.y.abs() < WALL_HEIGHT
+    }
+}
+
+/// A wall that is not really there.
+#[derive(Component, Debug)]
+pub struct SecretWall;
+
+/// Material shared by every secret wall, so they all flicker together.
+#[derive(Resource)]
+pub struct SecretWallMaterial(pub Handle<StandardMaterial>);
+
+/// Spawns a door panel across each one-way passage and a wall, minus its collider,
+/// across each secret one.
+pub fn spawn_gates(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    maze: Res<Maze>,
+) {
+    let panel_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.3, 0.3, 0.35),
+        ..default()
+    });
+    // A green strip on the side it opens from, a red one on the side it stays shut from
+    let entry_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.1, 0.8, 0.3),
+        emissive: LinearRgba::new(0.0, 0.8, 0.2, 1.0),
+        ..default()
+    });
+    let exit_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.8, 0.1, 0.1),
+        emissive: LinearRgba::new(0.8, 0.0, 0.0, 1.0),
+        ..default()
+    });
+    let secret_material = materials.add(StandardMaterial {
+        base_color: WALL_COLOR,
+        emissive: WALL_EMISSIVE,
+        ..default()
+    });
+    commands.insert_resource(SecretWallMaterial(secret_material.clone()));
+
+    let forward = maze.topology().forward_directions();
+    for y in 0..maze.height() {
+        for x in 0..maze.width() {
+            for &direction in forward {
+                let Some(other) = maze.step(x, y, direction) else { continue };
+                let (one_way, secret) = (maze.is_one_way(x, y, direction), maze.is_secret(x, y, direction));
+                if !one_way && !secret {
+                    continue;
+                }
+                let (transform, length) = wall_placement(&maze, x, y, direction);
+                let panel = meshes.add(Cuboid::new(length, WALL_HEIGHT, WALL_THICKNESS));
+
+                if one_way {
+                    let entry = if maze.cell(x, y).is_open(direction) { (x, y) } else { other };
+                    // Which face of the panel looks into the entry cell
+                    let normal = transform.rotation * Vec3::Z;
+                    let to_entry = cell_center(&maze, entry.0, entry.1) - transform.translation;
+                    let facing = to_entry.dot(normal).signum();
+                    let strip = meshes.add(Cuboid::new(length * 0.6, 0.1, 0.02));
+                    let strip_offset = WALL_THICKNESS / 2.0 + 0.01;
+
+                    commands
+                        .spawn((
+                            OneWayDoor { entry, length },
+                            Mesh3d(panel),
+                            MeshMaterial3d(panel_material.clone()),
+                            transform,
+                            Collider::cuboid(length / 2.0, WALL_HEIGHT / 2.0, WALL_THICKNESS / 2.0),
+                            RigidBody::Fixed,
+                        ))
+                        .with_children(|door| {
+                            for (side, material) in [(facing, &entry_material), (-facing, &exit_material)] {
+                                door.spawn((
+                                    Mesh3d(strip.clone()),
+                                    MeshMaterial3d(material.clone()),
+                                    Transform::from_xyz(0.0, 0.3, side * strip_offset),
+                                ));
+                            }
+                        });
+                } else {
+                    commands.spawn((
+                        SecretWall,
+                        WallSlot { x, y, direction },
+                        Mesh3d(panel),
+                        MeshMaterial3d(secret_material.clone()),
+                        transform,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Opens one-way doors while the player is in their entry cell, and keeps an open door
+/// open until the player has stepped clear of it, so it never shuts on them mid-way.
+/// A shut door is never opened from the exit side.
+pub fn pass_one_way_doors(
+    mut commands: Commands,
+    maze: Res<Maze>,
+    player_query: Query<&Transform, (With<Player>, Without<OneWayDoor>)>,
+    door_query: Query<(Entity, &OneWayDoor, &Transform, Has<ColliderDisabled>)>,
+) {
+    let Ok(player_transform) = player_query.get_single() else { return; };
+    let position = player_transform.translation;
+    let player = cell_at(&maze, position);
+
+    for (entity, door, transform, disabled) in door_query.iter() {
+        let open = player == Some(door.entry) || (disabled && door.touches(transform, position));
+        if open && !disabled {
+            commands.entity(entity).insert(ColliderDisabled);
+        } else if !open && disabled {
+            commands.entity(entity).remove::<ColliderDisabled>();
+        }
+    }
+}
+
+/// Wobbles the glow of every secret wall slightly, the only tell that it is not solid.
+pub fn flicker_secret_walls(
+    time: Res<Time>,
+    secret_material: Option<Res<SecretWallMaterial>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Some(secret_material) = secret_material else { return; };
+    let Some(material) = materials.get_mut(&secret_material.0) else { return; };
+    // Two sines at unrelated rates never settle into an obvious rhythm.
+    let t = time.elapsed_secs();
+    let wobble = (t * 23.0).sin() * (t * 7.3).sin();
+    material.emissive = WALL_EMISSIVE * (1.0 + FLICKER_DEPTH * wobble);
+}
+
+/// Opens a secret wall for good when E is pressed next to it.
+pub fn reveal_secret_walls(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut maze: ResMut<Maze>,
+    player_query: Query<&Transform, With<Player>>,
+    wall_query: Query<(Entity, &Transform, &WallSlot), With<SecretWall>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyE) { return; }
+    let Ok(player_transform) = player_query.get_single() else { return; };
+
+    for (entity, transform, slot) in wall_query.iter() {
+        let offset = player_transform.translation - transform.translation;
+        if offset.xz().length() <= REVEAL_RANGE && offset.y.abs() <= WALL_HEIGHT {
+            maze.reveal_passage(slot.x, slot.y, slot.direction);
+            commands.entity(entity).despawn();
+            info!("Found a secret passage");
+        }
+    }
+}
+
+/// Clears away every one-way door and secret wall for a restart, so `spawn_gates` can
+/// put them back from the restored maze.
+pub fn clear_gates(mut commands: Commands, query: Query<Entity, Or<(With<OneWayDoor>, With<SecretWall>)>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_door_is_touched_until_the_capsule_clears_it() {
+        let door = OneWayDoor { entry: (0, 0), length: 2.0 };
+        let transform = Transform::from_xyz(1.0, WALL_HEIGHT / 2.0, 0.0);
+        let at = |z: f32| Vec3::new(1.0, 0.5, z);
+        assert!(door.touches(&transform, at(0.0)));
+        assert!(door.touches(&transform, at(WALL_THICKNESS / 2.0 + PLAYER_RADIUS * 0.9)));
+        assert!(!door.touches(&transform, at(WALL_THICKNESS / 2.0 + PLAYER_RADIUS * 1.1)));
+        // Past the end of the panel, and on the floor above.
+        assert!(!door.touches(&transform, Vec3::new(2.0 + PLAYER_RADIUS * 1.1, 0.5, 0.0)));
+        assert!(!door.touches(&transform, at(0.0) + Vec3::Y * WALL_HEIGHT * 1.5));
+    }
+
+    #[test]
+    fn turned_doors_measure_across_their_own_face() {
+        let door = OneWayDoor { entry: (0, 0), length: 2.0 };
+        let transform = Transform::from_xyz(0.0, WALL_HEIGHT / 2.0, 0.0)
+            .with_rotation(Quat::from_rotation_y(std::f32::consts::FRAC_PI_2));
+        assert!(door.touches(&transform, Vec3::new(0.0, 0.5, 0.9)));
+        assert!(!door.touches(&transform, Vec3::new(PLAYER_RADIUS + WALL_THICKNESS, 0.5, 0.0)));
+    }
+}
@@ -1,94 +1,408 @@
-//! Maze generation using recursive backtracker algorithm.
-//! Produces a 20x20 grid where each cell tracks which walls are open.
+//! Maze grid, direction flags, and generation entry points.
+//! Produces a grid of any size where each cell tracks which walls are open.
+//! The carving itself lives in `algorithms`.
 
+use crate::maze::algorithms::{reconnect, MazeAlgorithm, RecursiveBacktracker};
+use crate::maze::doors::{Door, Keycard};
+use crate::maze::floors::FloorLink;
+use crate::maze::mask::Mask;
+use crate::maze::portals::Portal;
+use crate::maze::rooms::Room;
+use crate::maze::tiles::TileKind;
+use crate::maze::topology::Topology;
+use crate::rng::Rng;
+
+/// Default maze dimensions used when a run does not ask for a size.
 pub const MAZE_WIDTH: usize = 20;
 pub const MAZE_HEIGHT: usize = 20;
 
-pub const NORTH: u8 = 0b0001;
-pub const SOUTH: u8 = 0b0010;
-pub const EAST:  u8 = 0b0100;
-pub const WEST:  u8 = 0b1000;
+pub const NORTH: u16 = 0b0001;
+pub const SOUTH: u16 = 0b0010;
+pub const EAST:  u16 = 0b0100;
+pub const WEST:  u16 = 0b1000;
+/// Stairwell or lift to the floor above / below.
+pub const UP:    u16 = 0b0001_0000;
+pub const DOWN:  u16 = 0b0010_0000;
+/// Diagonal sides, only used by hex grids.
+pub const NORTH_EAST: u16 = 0b0100_0000;
+pub const NORTH_WEST: u16 = 0b1000_0000;
+pub const SOUTH_EAST: u16 = 0b0001_0000_0000;
+pub const SOUTH_WEST: u16 = 0b0010_0000_0000;
 
-/// A single maze cell storing which walls are open as a bitmask.
-#[derive(Clone, Copy, Debug, Default)]
+/// A single maze cell storing which walls are open as a bitmask, and its floor tile.
+/// Passages are stored per side, so a side open here but shut in the neighbor is a
+/// one-way passage out of this cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct Cell {
-    pub passages: u8,
+    pub passages: u16,
+    /// Open sides drawn as solid walls.
+    pub secret: u16,
+    pub tile: TileKind,
 }
 
 impl Cell {
     /// Returns true if the given direction is open.
-    pub fn is_open(&self, direction: u8) -> bool {
+    pub fn is_open(&self, direction: u16) -> bool {
         self.passages & direction != 0
     }
 }
 
 /// The full maze grid stored as a Bevy resource.
-#[derive(bevy::prelude::Resource, Debug)]
+/// Cells are kept row-major on the heap so any size can be used.
+/// Multi-floor mazes stack their floors along y: floor 0 is rows `0..rows_per_floor`,
+/// floor 1 the next band, and so on, with NORTH/SOUTH never crossing a band edge.
+/// Cells outside the mask are void: they have no neighbors and are never carved or drawn.
+#[derive(bevy::prelude::Resource, Clone, Debug, PartialEq, Eq)]
 pub struct Maze {
-    pub cells: [[Cell; MAZE_WIDTH]; MAZE_HEIGHT],
+    width: usize,
+    height: usize,
+    floors: usize,
+    topology: Topology,
+    cells: Vec<Cell>,
+    mask: Vec<bool>,
+    links: Vec<FloorLink>,
+    portals: Vec<Portal>,
+    rooms: Vec<Room>,
+    doors: Vec<Door>,
+    keycards: Vec<Keycard>,
 }
 
-/// Simple LCG pseudo-random number generator seeded by system time.
-struct Rng(u64);
-
-impl Rng {
-    /// Creates a new RNG with a fixed seed for reproducibility.
-    fn new(seed: u64) -> Self { Rng(seed) }
+/// The vertical links between floors, which every topology has.
+pub const VERTICAL_DIRECTIONS: [u16; 2] = [UP, DOWN];
 
-    /// Returns the next pseudo-random usize in range [0, max).
-    fn next_usize(&mut self, max: usize) -> usize {
-        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
-        ((self.0 >> 33) as usize) % max
+/// Returns the direction pointing back the way `direction` came.
+pub fn opposite(direction: u16) -> u16 {
+    match direction {
+        NORTH => SOUTH,
+        SOUTH => NORTH,
+        EAST => WEST,
+        WEST => EAST,
+        UP => DOWN,
+        DOWN => UP,
+        NORTH_EAST => SOUTH_WEST,
+        SOUTH_WEST => NORTH_EAST,
+        NORTH_WEST => SOUTH_EAST,
+        SOUTH_EAST => NORTH_WEST,
+        _ => panic!("not a single direction: {direction:#06b}"),
     }
 }
 
 impl Maze {
-    /// Generates a new maze using recursive backtracker (DFS).
-    pub fn generate(seed: u64) -> Self {
-        let mut cells = [[Cell::default(); MAZE_WIDTH]; MAZE_HEIGHT];
-        let mut visited = [[false; MAZE_WIDTH]; MAZE_HEIGHT];
-        let mut stack: Vec<(usize, usize)> = Vec::new();
-        let mut rng = Rng::new(seed);
+    /// Creates a fully walled single-floor maze of the given size.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::new_floors(width, height, 1)
+    }
+
+    /// Creates a fully walled maze of `floors` stacked floors, each `width` x `rows_per_floor`.
+    pub fn new_floors(width: usize, rows_per_floor: usize, floors: usize) -> Self {
+        Self::new_shaped(width, rows_per_floor, floors, Topology::Square)
+    }
+
+    /// Creates a fully walled maze of `floors` stacked floors laid out as `topology`.
+    /// Polar mazes need at least three sectors so each ring is a proper loop.
+    pub fn new_shaped(width: usize, rows_per_floor: usize, floors: usize, topology: Topology) -> Self {
+        assert!(width > 0 && rows_per_floor > 0 && floors > 0, "maze must be at least 1x1x1");
+        assert!(topology != Topology::Polar || width >= 3, "polar mazes need at least 3 sectors");
+        Maze {
+            width,
+            height: rows_per_floor * floors,
+            floors,
+            topology,
+            cells: vec![Cell::default(); width * rows_per_floor * floors],
+            mask: vec![true; width * rows_per_floor * floors],
+            links: Vec::new(),
+            portals: Vec::new(),
+            rooms: Vec::new(),
+            doors: Vec::new(),
+            keycards: Vec::new(),
+        }
+    }
 
-        visited[0][0] = true;
-        stack.push((0, 0));
-
-        while let Some(&(x, y)) = stack.last() {
-            let neighbors = unvisited_neighbors(x, y, &visited);
-            if neighbors.is_empty() {
-                stack.pop();
-            } else {
-                let idx = rng.next_usize(neighbors.len());
-                let (nx, ny, dir, opposite) = neighbors[idx];
-                cells[y][x].passages |= dir;
-                cells[ny][nx].passages |= opposite;
-                visited[ny][nx] = true;
-                stack.push((nx, ny));
+    /// Creates a fully walled single-floor maze shaped like `mask`.
+    pub fn from_mask(mask: &Mask) -> Self {
+        let mut maze = Self::new(mask.width(), mask.height());
+        for y in 0..mask.height() {
+            for x in 0..mask.width() {
+                maze.set_in_mask(x, y, mask.contains(x, y));
             }
         }
+        maze
+    }
 
-        Maze { cells }
+    /// Number of cells along the x axis.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Number of cells along the y axis, counting every floor.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of stacked floors.
+    pub fn floors(&self) -> usize {
+        self.floors
+    }
+
+    /// How the cells tile.
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Planar directions a cell can open in this maze's topology.
+    pub fn directions(&self) -> &'static [u16] {
+        self.topology.directions()
+    }
+
+    /// Number of rows that make up a single floor.
+    pub fn rows_per_floor(&self) -> usize {
+        self.height / self.floors
+    }
+
+    /// Which floor row `y` belongs to. Floor 0 is the top (start) floor.
+    pub fn floor_of(&self, y: usize) -> usize {
+        y / self.rows_per_floor()
+    }
+
+    /// Row `y` measured from the north edge of its own floor.
+    pub fn row_on_floor(&self, y: usize) -> usize {
+        y % self.rows_per_floor()
+    }
+
+    /// Stairwells and lifts connecting the floors.
+    pub fn links(&self) -> &[FloorLink] {
+        &self.links
+    }
+
+    /// Mutable access to the floor links, used when building multi-floor mazes.
+    pub(crate) fn links_mut(&mut self) -> &mut Vec<FloorLink> {
+        &mut self.links
+    }
+
+    /// Pairs of cells joined by a network jump.
+    pub fn portals(&self) -> &[Portal] {
+        &self.portals
+    }
+
+    /// Mutable access to the portals, used when linking them.
+    pub(crate) fn portals_mut(&mut self) -> &mut Vec<Portal> {
+        &mut self.portals
+    }
+
+    /// Open rooms carved into the grid by the rooms-and-corridors generator.
+    pub fn rooms(&self) -> &[Room] {
+        &self.rooms
+    }
+
+    /// Mutable access to the room list, used by generators.
+    pub(crate) fn rooms_mut(&mut self) -> &mut Vec<Room> {
+        &mut self.rooms
+    }
+
+    /// Locked doors across passages.
+    pub fn doors(&self) -> &[Door] {
+        &self.doors
+    }
+
+    /// Mutable access to the doors, used when locking a maze.
+    pub(crate) fn doors_mut(&mut self) -> &mut Vec<Door> {
+        &mut self.doors
+    }
+
+    /// Keycards lying in the maze, one per door color.
+    pub fn keycards(&self) -> &[Keycard] {
+        &self.keycards
+    }
+
+    /// Mutable access to the keycards, used when locking a maze.
+    pub(crate) fn keycards_mut(&mut self) -> &mut Vec<Keycard> {
+        &mut self.keycards
+    }
+
+    /// The room containing (x, y), if any.
+    pub fn room_at(&self, x: usize, y: usize) -> Option<&Room> {
+        self.rooms.iter().find(|room| room.contains(x, y))
     }
-}
 
-/// Returns all unvisited neighbors of (x, y) with direction flags.
-fn unvisited_neighbors(
-    x: usize,
-    y: usize,
-    visited: &[[bool; MAZE_WIDTH]; MAZE_HEIGHT],
-) -> Vec<(usize, usize, u8, u8)> {
-    let mut neighbors = Vec::new();
-    if y > 0 && !visited[y - 1][x] {
-        neighbors.push((x, y - 1, NORTH, SOUTH));
+    /// Returns true if (x, y) is a real cell rather than void outside the mask.
+    pub fn in_mask(&self, x: usize, y: usize) -> bool {
+        self.mask[self.index(x, y)]
     }
-    if y + 1 < MAZE_HEIGHT && !visited[y + 1][x] {
-        neighbors.push((x, y + 1, SOUTH, NORTH));
+
+    /// Adds (x, y) to the mask or turns it into void, sealing it off from its neighbors.
+    pub(crate) fn set_in_mask(&mut self, x: usize, y: usize, inside: bool) {
+        if !inside {
+            for &dir in self.directions().iter().chain(&VERTICAL_DIRECTIONS) {
+                self.close(x, y, dir);
+            }
+        }
+        let idx = self.index(x, y);
+        self.mask[idx] = inside;
+    }
+
+    /// Returns true if some cells are void.
+    pub fn is_masked(&self) -> bool {
+        self.mask.contains(&false)
+    }
+
+    /// Number of cells inside the mask.
+    pub fn cell_count(&self) -> usize {
+        self.mask.iter().filter(|&&inside| inside).count()
+    }
+
+    /// The first cell inside the mask in row-major order; (0, 0) for unmasked mazes.
+    pub fn first_cell(&self) -> (usize, usize) {
+        let idx = self.mask.iter().position(|&inside| inside).expect("mask has at least one cell");
+        (idx % self.width, idx / self.width)
+    }
+
+    /// Returns the cell at (x, y).
+    pub fn cell(&self, x: usize, y: usize) -> Cell {
+        self.cells[self.index(x, y)]
+    }
+
+    /// Returns a mutable reference to the cell at (x, y).
+    pub fn cell_mut(&mut self, x: usize, y: usize) -> &mut Cell {
+        let idx = self.index(x, y);
+        &mut self.cells[idx]
+    }
+
+    /// Converts (x, y) into an index into the row-major cell storage.
+    pub fn index(&self, x: usize, y: usize) -> usize {
+        assert!(x < self.width && y < self.height, "cell ({x}, {y}) out of bounds");
+        y * self.width + x
+    }
+
+    /// Returns the cell one step from (x, y) in `direction`, if it is inside the grid.
+    /// UP and DOWN land on the same (x, row) of the neighboring floor; planar steps
+    /// follow the maze's topology and never leave their floor.
+    /// Void cells have no neighbors and are never stepped into.
+    pub fn step(&self, x: usize, y: usize, direction: u16) -> Option<(usize, usize)> {
+        let rows = self.rows_per_floor();
+        if !self.in_mask(x, y) {
+            return None;
+        }
+        let next = match direction {
+            UP if y >= rows => Some((x, y - rows)),
+            DOWN if y + rows < self.height => Some((x, y + rows)),
+            UP | DOWN => None,
+            _ => {
+                let floor_start = y - self.row_on_floor(y);
+                self.topology
+                    .step(x, self.row_on_floor(y), self.width, rows, direction)
+                    .map(|(nx, row)| (nx, floor_start + row))
+            }
+        };
+        next.filter(|&(nx, ny)| self.in_mask(nx, ny))
+    }
+
+    /// Returns every in-bounds neighbor of (x, y) on the same floor with the direction
+    /// leading to it, regardless of walls.
+    pub fn neighbors(&self, x: usize, y: usize) -> Vec<(usize, usize, u16)> {
+        self.directions()
+            .iter()
+            .filter_map(|&dir| self.step(x, y, dir).map(|(nx, ny)| (nx, ny, dir)))
+            .collect()
+    }
+
+    /// Opens the wall between (x, y) and its neighbor in `direction` on both sides.
+    pub fn carve(&mut self, x: usize, y: usize, direction: u16) {
+        let (nx, ny) = self
+            .step(x, y, direction)
+            .expect("cannot carve through the outer wall");
+        self.cell_mut(x, y).passages |= direction;
+        self.cell_mut(nx, ny).passages |= opposite(direction);
+    }
+
+    /// Closes the wall between (x, y) and its neighbor in `direction` on both sides.
+    /// A real wall is never secret, so any secret flag goes too.
+    pub fn close(&mut self, x: usize, y: usize, direction: u16) {
+        if let Some((nx, ny)) = self.step(x, y, direction) {
+            self.cell_mut(nx, ny).passages &= !opposite(direction);
+        }
+        self.cell_mut(x, y).passages &= !direction;
+        self.reveal_passage(x, y, direction);
+    }
+
+    /// Returns every cell with exactly one open passage.
+    pub fn dead_ends(&self) -> Vec<(usize, usize)> {
+        let mut dead_ends = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.cell(x, y).passages.count_ones() == 1 {
+                    dead_ends.push((x, y));
+                }
+            }
+        }
+        dead_ends
+    }
+
+    /// Returns the neighbors of (x, y) that can be walked to through open passages,
+    /// including stairwells and lifts to other floors and the far end of a portal.
+    /// A one-way passage only leads out of the cell it is open in, and secret walls
+    /// count as open.
+    pub fn open_neighbors(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let cell = self.cell(x, y);
+        self.directions()
+            .iter()
+            .chain(&VERTICAL_DIRECTIONS)
+            .filter(|&&dir| cell.is_open(dir))
+            .filter_map(|&dir| self.step(x, y, dir))
+            .chain(self.portal_partner(x, y))
+            .collect()
+    }
+
+    /// Returns true if every cell in the mask can be reached from the first one through
+    /// open passages.
+    pub fn is_connected(&self) -> bool {
+        self.distances(self.first_cell()).reachable_count() == self.cell_count()
+    }
+
+    /// Generates a new maze of the default size using recursive backtracker (DFS).
+    pub fn generate(seed: u64) -> Self {
+        Self::generate_sized(MAZE_WIDTH, MAZE_HEIGHT, seed)
+    }
+
+    /// Generates a new maze of the given size using recursive backtracker (DFS).
+    pub fn generate_sized(width: usize, height: usize, seed: u64) -> Self {
+        Self::generate_with(width, height, seed, &RecursiveBacktracker)
+    }
+
+    /// Generates a new maze of the given size with any carving algorithm.
+    pub fn generate_with(
+        width: usize,
+        height: usize,
+        seed: u64,
+        algorithm: &dyn MazeAlgorithm,
+    ) -> Self {
+        Self::generate_shaped(width, height, Topology::Square, seed, algorithm)
+    }
+
+    /// Generates a new single-floor maze laid out as `topology` with any carving algorithm.
+    pub fn generate_shaped(
+        width: usize,
+        height: usize,
+        topology: Topology,
+        seed: u64,
+        algorithm: &dyn MazeAlgorithm,
+    ) -> Self {
+        Self::carve_from(Maze::new_shaped(width, height, 1, topology), seed, algorithm)
     }
-    if x + 1 < MAZE_WIDTH && !visited[y][x + 1] {
-        neighbors.push((x + 1, y, EAST, WEST));
+
+    /// Generates a single-floor maze inside `mask` with any carving algorithm.
+    pub fn generate_masked(mask: &Mask, seed: u64, algorithm: &dyn MazeAlgorithm) -> Self {
+        Self::carve_from(Maze::from_mask(mask), seed, algorithm)
     }
-    if x > 0 && !visited[y][x - 1] {
-        neighbors.push((x - 1, y, WEST, EAST));
+
+    /// Carves a walled maze. Row-based carvers cannot see around void cells or past
+    /// square sides, so any regions they leave split get stitched back together afterwards.
+    fn carve_from(mut maze: Maze, seed: u64, algorithm: &dyn MazeAlgorithm) -> Self {
+        let mut rng = Rng::new(seed);
+        algorithm.carve(&mut maze, &mut rng);
+        if !maze.is_connected() {
+            reconnect(&mut maze, &mut rng);
+        }
+        debug_assert!(maze.is_connected(), "maze algorithm left unreachable cells");
+        maze
     }
-    neighbors
-}
\ No newline at end of file
+}
@@ -0,0 +1,162 @@
+//! Glitch walls — once the clock is past half time, walls flicker open and shut,
+//! faster and in bigger bursts as it runs out. The terminal always stays reachable.
+
+use std::collections::BTreeSet;
+use bevy::prelude::*;
+use crate::game_timer::GameTimer;
+use crate::maze::generator::Maze;
+use crate::maze::keycards::Keyring;
+use crate::maze::placement::Placements;
+use crate::maze::renderer::{spawn_wall_batch, WallBatch, WallMaterial, WallSlot};
+use crate::maze::spatial::cell_at;
+use crate::maze::shifting::WallChange;
+use crate::player::controller::Player;
+use crate::rng::{GameRng, RngStream};
+
+/// Fraction of the timer left when glitches start.
+pub const GLITCH_START: f32 = 0.5;
+
+/// Seconds between glitches when they start; shrinks to a quarter of this at zero.
+pub const GLITCH_INTERVAL: f32 = 4.0;
+
+/// Walls toggled per glitch when they start; grows by up to GLITCH_EXTRA_WALLS at zero.
+pub const GLITCH_WALLS: usize = 2;
+pub const GLITCH_EXTRA_WALLS: usize = 4;
+
+/// Fired after a glitch rearranges walls, for audio and UI to react to.
+#[derive(Event, Debug)]
+pub struct MazeGlitched {
+    pub changes: Vec<WallChange>,
+}
+
+/// Countdown to the next glitch.
+#[derive(Resource, Debug)]
+pub struct GlitchClock {
+    pub until_next: f32,
+}
+
+impl Default for GlitchClock {
+    fn default() -> Self {
+        GlitchClock { until_next: GLITCH_INTERVAL }
+    }
+}
+
+/// The maze as the run started, before glitches, keycards or secret walls changed it,
+/// kept so a restart plays the same maze again.
+#[derive(Resource, Debug)]
+pub struct StartingMaze(pub Maze);
+
+/// Toggles a few walls whenever the glitch clock runs out, updating the maze
+/// resource and rebuilding the wall batches they fall in to match.
+#[allow(clippy::too_many_arguments)]
+pub fn glitch_walls(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    wall_material: Res<WallMaterial>,
+    time: Res<Time>,
+    timer: Res<GameTimer>,
+    mut clock: ResMut<GlitchClock>,
+    mut maze: ResMut<Maze>,
+    mut rng: ResMut<GameRng>,
+    placements: Res<Placements>,
+    keyring: Res<Keyring>,
+    player_query: Query<&Transform, With<Player>>,
+    wall_query: Query<(Entity, &WallBatch)>,
+    mut events: EventWriter<MazeGlitched>,
+) {
+    let left = timer.remaining / timer.total;
+    if left > GLITCH_START { return; }
+
+    clock.until_next -= time.delta_secs();
+    if clock.until_next > 0.0 { return; }
+
+    // 0 when glitches begin, 1 when the clock hits zero
+    let pressure = 1.0 - left / GLITCH_START;
+    clock.until_next = GLITCH_INTERVAL * (1.0 - 0.75 * pressure);
+
+    let Ok(player_transform) = player_query.get_single() else { return; };
+    let Some(player) = cell_at(&maze, player_transform.translation) else { return; };
+
+    let count = GLITCH_WALLS + (pressure * GLITCH_EXTRA_WALLS as f32) as usize;
+    let changes = maze.shift_walls(
+        count,
+        player,
+        placements.terminal,
+        &keyring.held,
+        rng.stream(RngStream::Maze),
+    );
+    if changes.is_empty() { return; }
+
+    let stale: BTreeSet<_> = changes
+        .iter()
+        .map(|change| {
+            let slot = WallSlot::of(&maze, change.x, change.y, change.direction);
+            maze.wall_batch_of(slot.x, slot.y)
+        })
+        .collect();
+    for (entity, _) in wall_query.iter().filter(|(_, batch)| stale.contains(&batch.0)) {
+        commands.entity(entity).despawn();
+    }
+    for key in stale {
+        spawn_wall_batch(&mut commands, &mut meshes, &wall_material.0, &maze, key, &maze.batch_walls(key));
+    }
+
+    info!("Glitch: {} walls shifted", changes.len());
+    events.send(MazeGlitched { changes });
+}
+
+/// Puts the maze back the way the run started and rebuilds every wall batch from it,
+/// with the glitch clock wound back to its first interval.
+pub fn restore_maze(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    wall_material: Res<WallMaterial>,
+    starting: Res<StartingMaze>,
+    mut maze: ResMut<Maze>,
+    mut clock: ResMut<GlitchClock>,
+    wall_query: Query<Entity, With<WallBatch>>,
+) {
+    *maze = starting.0.clone();
+    *clock = GlitchClock::default();
+    for entity in wall_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    for (key, walls) in maze.wall_batches() {
+        spawn_wall_batch(&mut commands, &mut meshes, &wall_material.0, &maze, key, &walls);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::algorithms::MazeAlgorithmKind;
+    use crate::maze::renderer::spawn_maze;
+    use crate::rng::Rng;
+
+    #[test]
+    fn restoring_rebuilds_the_carved_maze() {
+        let carved = Maze::generate_with(12, 12, 9, &MazeAlgorithmKind::Prim);
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .init_resource::<GlitchClock>()
+            .insert_resource(StartingMaze(carved.clone()))
+            .insert_resource(carved.clone())
+            .add_systems(Startup, spawn_maze);
+        app.update();
+
+        let world = app.world_mut();
+        let shifted = world.resource_mut::<Maze>().shift_walls(6, (0, 0), (11, 11), &[], &mut Rng::new(4));
+        assert!(!shifted.is_empty());
+        assert_ne!(*world.resource::<Maze>(), carved);
+        world.resource_mut::<GlitchClock>().until_next = 0.1;
+
+        world.run_system_cached(restore_maze).expect("restore runs");
+        assert_eq!(*world.resource::<Maze>(), carved);
+        assert_eq!(world.resource::<GlitchClock>().until_next, GLITCH_INTERVAL);
+        let mut batches: Vec<_> = world.query::<&WallBatch>().iter(world).map(|batch| batch.0).collect();
+        batches.sort_unstable();
+        assert_eq!(batches, carved.wall_batches().into_keys().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,82 @@
+//! Golden mazes — known seeds pinned to the exact layout they must produce.
+//! `cargo test` and `--check-golden` regenerate every fixture in GOLDEN_DIR and report
+//! any drift, so a change to the RNG or a carver never alters saved seeds unnoticed.
+
+use std::path::Path;
+use crate::maze::algorithms::MazeAlgorithmKind;
+use crate::maze::generator::Maze;
+
+/// Where the pinned mazes live. Each file is named `<algorithm>-<seed>.txt`.
+pub const GOLDEN_DIR: &str = "assets/mazes/golden";
+
+/// Splits a fixture file name like `hunt-and-kill-12345.txt` into algorithm and seed.
+pub fn parse_fixture_name(name: &str) -> Option<(MazeAlgorithmKind, u64)> {
+    let (algorithm, seed) = name.strip_suffix(".txt")?.rsplit_once('-')?;
+    Some((MazeAlgorithmKind::from_name(algorithm)?, seed.parse().ok()?))
+}
+
+/// Regenerates every fixture in `dir` at its own size and compares the layouts.
+/// Returns how many fixtures were checked, or one message per fixture that failed.
+pub fn check_golden(dir: &Path) -> Result<usize, Vec<String>> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|err| vec![format!("{}: {}", dir.display(), err)])?;
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".txt"))
+        .collect();
+    names.sort();
+
+    let mut failures = Vec::new();
+    for name in &names {
+        let Some((algorithm, seed)) = parse_fixture_name(name) else {
+            failures.push(format!("{name}: expected <algorithm>-<seed>.txt"));
+            continue;
+        };
+        let expected = match std::fs::read_to_string(dir.join(name))
+            .map_err(|err| err.to_string())
+            .and_then(|text| Maze::from_ascii(&text).map_err(|err| err.to_string()))
+        {
+            Ok(maze) => maze,
+            Err(err) => {
+                failures.push(format!("{name}: {err}"));
+                continue;
+            }
+        };
+
+        // Compare rendered text so the check sees exactly what a fixture file pins.
+        let actual = Maze::generate_with(expected.width(), expected.height(), seed, &algorithm);
+        if actual.to_ascii() != expected.to_ascii() {
+            failures.push(format!("{name}: seed {seed} no longer produces this maze"));
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(names.len())
+    } else {
+        Err(failures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn golden_mazes_still_generate() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(GOLDEN_DIR);
+        match check_golden(&dir) {
+            Ok(count) => assert!(count > 0, "no fixtures in {}", dir.display()),
+            Err(failures) => panic!("golden mazes drifted:\n{}", failures.join("\n")),
+        }
+    }
+
+    #[test]
+    fn fixture_names_split_at_the_last_dash() {
+        assert_eq!(parse_fixture_name("hunt-and-kill-12345.txt"), Some((MazeAlgorithmKind::HuntAndKill, 12345)));
+        assert_eq!(parse_fixture_name("backtracker-1.txt"), Some((MazeAlgorithmKind::RecursiveBacktracker, 1)));
+        assert_eq!(parse_fixture_name("backtracker.txt"), None);
+        assert_eq!(parse_fixture_name("maze-runner-1.txt"), None);
+    }
+}
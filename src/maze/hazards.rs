@@ -0,0 +1,51 @@
+//! Hazard tiles in play — sparks drain the clock, floods slow you down, dark cells
+//! kill the lights and static scrambles the HUD.
+
+use bevy::prelude::*;
+use crate::game_timer::GameTimer;
+use crate::maze::generator::Maze;
+use crate::maze::renderer::AMBIENT_BRIGHTNESS;
+use crate::maze::spatial::cell_at;
+use crate::maze::tiles::TileKind;
+use crate::player::controller::Player;
+
+/// Seconds knocked off the clock for every second spent on a sparking tile.
+/// The clock is the player's health until Phase 2 adds real damage.
+pub const SPARK_DRAIN: f32 = 2.0;
+
+/// Movement speed multiplier while wading through a flooded cell.
+pub const FLOOD_SLOWDOWN: f32 = 0.5;
+
+/// Ambient light left in a dark cell.
+pub const DARK_BRIGHTNESS: f32 = 0.02;
+
+/// The tile under the player's feet, updated every frame.
+#[derive(Resource, Debug, Default, PartialEq, Eq)]
+pub struct CurrentTile(pub TileKind);
+
+/// Looks up which tile the player is standing on.
+pub fn track_player_tile(
+    maze: Res<Maze>,
+    player_query: Query<&Transform, With<Player>>,
+    mut current: ResMut<CurrentTile>,
+) {
+    let Ok(transform) = player_query.get_single() else { return; };
+    let tile = cell_at(&maze, transform.translation).map_or(TileKind::Plain, |(x, y)| maze.tile(x, y));
+    // Only write on a change so other systems can react to `is_changed`.
+    if current.0 != tile {
+        current.0 = tile;
+    }
+}
+
+/// Drains the clock while the player stands on sparks.
+pub fn spark_damage(time: Res<Time>, current: Res<CurrentTile>, mut timer: ResMut<GameTimer>) {
+    if current.0 == TileKind::Sparking {
+        timer.remaining = (timer.remaining - SPARK_DRAIN * time.delta_secs()).max(0.0);
+    }
+}
+
+/// Dims the ambient light while the player is in a dark cell.
+pub fn darken_lights(current: Res<CurrentTile>, mut ambient: ResMut<AmbientLight>) {
+    if !current.is_changed() { return; }
+    ambient.brightness = if current.0 == TileKind::Dark { DARK_BRIGHTNESS } else { AMBIENT_BRIGHTNESS };
+}
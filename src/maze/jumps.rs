@@ -0,0 +1,135 @@
+//! Network jumps in play — step onto a portal pad and you (or an enemy) come out of its
+//! partner, with a brief glitch over the screen when it is you.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use crate::enemies::types::Enemy;
+use crate::maze::generator::Maze;
+use crate::maze::portals::Portal;
+use crate::maze::renderer::WALL_HEIGHT;
+use crate::maze::spatial::{cell_at, cell_center, CELL_SIZE};
+use crate::player::controller::Player;
+
+/// How close to the middle of a portal cell something must get to jump.
+pub const JUMP_RANGE: f32 = 0.5;
+
+/// How long the screen glitches after the player jumps, in seconds.
+pub const JUMP_GLITCH_SECS: f32 = 0.4;
+
+/// The glowing pad at either end of a portal.
+#[derive(Component)]
+pub struct PortalPad;
+
+/// Set on anything that just came out of a portal, so it is not sent straight back.
+/// Removed once it steps off the pad it landed on.
+#[derive(Component, Debug)]
+pub struct JumpCooldown {
+    pub landed: (usize, usize),
+}
+
+/// Time left on the player's jump glitch.
+#[derive(Resource, Debug, Default)]
+pub struct JumpGlitch {
+    pub remaining: f32,
+}
+
+impl JumpGlitch {
+    /// How strong the glitch is right now, from 1 just after a jump down to 0.
+    pub fn strength(&self) -> f32 {
+        (self.remaining / JUMP_GLITCH_SECS).clamp(0.0, 1.0)
+    }
+}
+
+/// Spawns a pad and a faint column of light on both ends of every portal.
+pub fn spawn_portals(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    maze: Res<Maze>,
+) {
+    let pad = meshes.add(Cylinder::new(CELL_SIZE * 0.35, 0.04));
+    let column = meshes.add(Cylinder::new(CELL_SIZE * 0.3, WALL_HEIGHT));
+
+    for (index, portal) in maze.portals().iter().enumerate() {
+        let [r, g, b] = Portal::color(index);
+        let color = Color::srgb_u8(r, g, b);
+        let pad_material = materials.add(StandardMaterial {
+            base_color: color,
+            emissive: LinearRgba::from(color),
+            ..default()
+        });
+        let column_material = materials.add(StandardMaterial {
+            base_color: color.with_alpha(0.15),
+            emissive: LinearRgba::from(color) * 0.3,
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        });
+
+        for (x, y) in [portal.a, portal.b] {
+            let center = cell_center(&maze, x, y);
+            commands.spawn((
+                PortalPad,
+                Mesh3d(pad.clone()),
+                MeshMaterial3d(pad_material.clone()),
+                Transform::from_translation(center + Vec3::Y * 0.02),
+            ));
+            commands.spawn((
+                PortalPad,
+                Mesh3d(column.clone()),
+                MeshMaterial3d(column_material.clone()),
+                Transform::from_translation(center + Vec3::Y * WALL_HEIGHT / 2.0),
+            ));
+        }
+    }
+}
+
+/// Moves the player and enemies that reach the middle of a portal cell to its partner,
+/// keeping their height above the floor.
+pub fn jump_portals(
+    mut commands: Commands,
+    maze: Res<Maze>,
+    mut glitch: ResMut<JumpGlitch>,
+    mut query: Query<
+        (Entity, &mut Transform, Option<&mut Velocity>, Option<&JumpCooldown>, Has<Player>),
+        Or<(With<Player>, With<Enemy>)>,
+    >,
+) {
+    for (entity, mut transform, velocity, cooldown, is_player) in query.iter_mut() {
+        let on_pad = |cell: (usize, usize)| {
+            (transform.translation - cell_center(&maze, cell.0, cell.1)).xz().length() <= JUMP_RANGE
+        };
+
+        if let Some(cooldown) = cooldown {
+            if on_pad(cooldown.landed) {
+                continue;
+            }
+            commands.entity(entity).remove::<JumpCooldown>();
+        }
+
+        let Some(cell) = cell_at(&maze, transform.translation) else { continue };
+        let Some(partner) = maze.portal_partner(cell.0, cell.1) else { continue };
+        if !on_pad(cell) {
+            continue;
+        }
+
+        let height = transform.translation.y - cell_center(&maze, cell.0, cell.1).y;
+        transform.translation = cell_center(&maze, partner.0, partner.1) + Vec3::Y * height;
+        if let Some(mut velocity) = velocity {
+            *velocity = Velocity::zero();
+        }
+        commands.entity(entity).insert(JumpCooldown { landed: partner });
+
+        if is_player {
+            glitch.remaining = JUMP_GLITCH_SECS;
+            info!("Network jump from {:?} to {:?}", cell, partner);
+        }
+    }
+}
+
+/// Runs the jump glitch down.
+pub fn tick_jump_glitch(time: Res<Time>, mut glitch: ResMut<JumpGlitch>) {
+    if glitch.remaining > 0.0 {
+        glitch.remaining = (glitch.remaining - time.delta_secs()).max(0.0);
+    }
+}
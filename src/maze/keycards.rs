@@ -0,0 +1,127 @@
+//! Doors and keycards in play — walk over a keycard to pick it up,
+//! walk up to a door of the same color to unlock it.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use crate::maze::doors::KeyColor;
+use crate::maze::generator::Maze;
+use crate::maze::renderer::{wall_placement, WALL_HEIGHT, WALL_THICKNESS};
+use crate::maze::spatial::cell_center;
+use crate::player::controller::Player;
+
+/// How close to a keycard the player must get to pick it up.
+pub const PICKUP_RANGE: f32 = 0.8;
+
+/// How close to a door the player must get for a matching keycard to open it.
+pub const UNLOCK_RANGE: f32 = 1.2;
+
+/// Keycards the player is carrying.
+#[derive(Resource, Default, Debug)]
+pub struct Keyring {
+    pub held: Vec<KeyColor>,
+}
+
+/// A locked door blocking a passage until its keycard is held.
+#[derive(Component)]
+pub struct LockedDoor(pub KeyColor);
+
+/// A keycard (a color-coded floppy) waiting to be picked up.
+#[derive(Component)]
+pub struct KeycardPickup(pub KeyColor);
+
+/// Spawns a solid door panel across each locked passage and a floppy for each keycard.
+pub fn spawn_doors_and_keycards(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    maze: Res<Maze>,
+) {
+    let material = |materials: &mut Assets<StandardMaterial>, color: KeyColor, glow: f32| {
+        let [r, g, b] = color.rgb();
+        let base_color = Color::srgb_u8(r, g, b);
+        materials.add(StandardMaterial {
+            base_color,
+            emissive: LinearRgba::from(base_color) * glow,
+            ..default()
+        })
+    };
+
+    for door in maze.doors() {
+        // The door stands exactly where the wall across its passage would.
+        let (transform, length) = wall_placement(&maze, door.x, door.y, door.direction);
+        commands.spawn((
+            LockedDoor(door.color),
+            Mesh3d(meshes.add(Cuboid::new(length, WALL_HEIGHT, WALL_THICKNESS))),
+            MeshMaterial3d(material(&mut materials, door.color, 0.2)),
+            transform,
+            Collider::cuboid(length / 2.0, WALL_HEIGHT / 2.0, WALL_THICKNESS / 2.0),
+            RigidBody::Fixed,
+        ));
+    }
+
+    for key in maze.keycards() {
+        commands.spawn((
+            KeycardPickup(key.color),
+            Mesh3d(meshes.add(Cuboid::new(0.35, 0.04, 0.35))),
+            MeshMaterial3d(material(&mut materials, key.color, 1.0)),
+            Transform::from_translation(cell_center(&maze, key.x, key.y) + Vec3::Y * 0.4),
+        ));
+    }
+}
+
+/// Picks up any keycard the player walks over.
+pub fn collect_keycards(
+    mut commands: Commands,
+    mut keyring: ResMut<Keyring>,
+    player_query: Query<&Transform, With<Player>>,
+    keycard_query: Query<(Entity, &Transform, &KeycardPickup)>,
+) {
+    let Ok(player_transform) = player_query.get_single() else { return; };
+
+    for (entity, transform, keycard) in keycard_query.iter() {
+        if near(player_transform, transform, PICKUP_RANGE) {
+            keyring.held.push(keycard.0);
+            commands.entity(entity).despawn();
+            info!("Picked up the {:?} keycard", keycard.0);
+        }
+    }
+}
+
+/// Removes a door, collider and all, once the player reaches it holding its keycard,
+/// and takes it out of the maze so routing and glitches see the way open.
+pub fn unlock_doors(
+    mut commands: Commands,
+    mut maze: ResMut<Maze>,
+    keyring: Res<Keyring>,
+    player_query: Query<&Transform, With<Player>>,
+    door_query: Query<(Entity, &Transform, &LockedDoor)>,
+) {
+    let Ok(player_transform) = player_query.get_single() else { return; };
+
+    for (entity, transform, door) in door_query.iter() {
+        if keyring.held.contains(&door.0) && near(player_transform, transform, UNLOCK_RANGE) {
+            commands.entity(entity).despawn();
+            maze.unlock(door.0);
+            info!("Unlocked the {:?} door", door.0);
+        }
+    }
+}
+
+/// Empties the keyring and clears away every door and keycard for a restart, so
+/// `spawn_doors_and_keycards` can put them back from the restored maze.
+pub fn clear_keycards(
+    mut commands: Commands,
+    mut keyring: ResMut<Keyring>,
+    query: Query<Entity, Or<(With<LockedDoor>, With<KeycardPickup>)>>,
+) {
+    keyring.held.clear();
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Within `range` across the floor, and on the same floor.
+fn near(player: &Transform, target: &Transform, range: f32) -> bool {
+    (player.translation - target.translation).xz().length() <= range
+        && (player.translation.y - target.translation.y).abs() <= WALL_HEIGHT
+}
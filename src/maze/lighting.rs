@@ -0,0 +1,125 @@
+//! Ceiling lights — fluorescent panels hung every few cells, some of them failing.
+//! Which panels flicker or are dead is fixed by the seed; how badly they flicker
+//! grows as the clock runs down. Lighting the panels in play lives in `lights`.
+
+use crate::maze::generator::Maze;
+use crate::maze::tiles::TileKind;
+use crate::rng::{splitmix64, Rng};
+
+/// Cells between ceiling panels, across and along each floor.
+pub const LIGHT_SPACING: usize = 3;
+
+/// Share of panels that flicker, and share that are dead.
+pub const FLICKERING_SHARE: f32 = 0.15;
+pub const DEAD_SHARE: f32 = 0.1;
+
+/// Times a second a flickering panel may cut out, when the clock is full; triples at zero.
+pub const FLICKER_RATE: f32 = 8.0;
+
+/// How a ceiling panel is holding up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PanelState {
+    Steady,
+    /// A failing starter; cuts out at random.
+    Flickering,
+    /// Burnt out, or over a dark tile with its fuse blown.
+    Dead,
+}
+
+impl PanelState {
+    /// Chance the panel is cut out at any moment, `pressure` being 0 on a full clock
+    /// and 1 at zero. Even steady panels start to stutter near the end.
+    pub fn dropout(self, pressure: f32) -> f32 {
+        let pressure = pressure.clamp(0.0, 1.0);
+        match self {
+            PanelState::Steady => 0.05 * pressure * pressure,
+            PanelState::Flickering => 0.2 + 0.5 * pressure,
+            PanelState::Dead => 1.0,
+        }
+    }
+}
+
+/// One ceiling panel over the middle of cell (x, y). `phase` keeps panels from
+/// flickering in step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LightPanel {
+    pub x: usize,
+    pub y: usize,
+    pub state: PanelState,
+    pub phase: u64,
+}
+
+impl LightPanel {
+    /// How brightly the panel shines `time` seconds into the run, from 0 (out) to 1.
+    /// The same time always gives the same level.
+    pub fn level(&self, time: f32, pressure: f32) -> f32 {
+        let dropout = self.state.dropout(pressure);
+        if dropout >= 1.0 {
+            return 0.0;
+        }
+        let rate = FLICKER_RATE * (1.0 + 2.0 * pressure.clamp(0.0, 1.0));
+        let step = (time.max(0.0) * rate) as u64;
+        let roll = (splitmix64(self.phase ^ step) >> 40) as f32 / (1u64 << 24) as f32;
+        // A cut-out panel does not go fully dark; the tube glows faintly.
+        if roll < dropout { 0.3 * roll / dropout } else { 1.0 }
+    }
+}
+
+impl Maze {
+    /// Hangs a panel over every LIGHT_SPACING-th cell along and across each floor,
+    /// skipping void cells, and rolls which ones flicker or are dead.
+    /// Panels over dark tiles are always dead.
+    pub fn place_light_panels(&self, rng: &mut Rng) -> Vec<LightPanel> {
+        let offset = LIGHT_SPACING / 2;
+        let mut panels = Vec::new();
+        for y in 0..self.height() {
+            let row = y - self.floor_of(y) * self.rows_per_floor();
+            if row % LIGHT_SPACING != offset {
+                continue;
+            }
+            for x in (offset..self.width()).step_by(LIGHT_SPACING) {
+                if !self.in_mask(x, y) {
+                    continue;
+                }
+                // Always draw both numbers so one panel's state never shifts the rest.
+                let roll = rng.next_f32();
+                let phase = splitmix64(u64::from(rng.next_u32()));
+                let state = if self.tile(x, y) == TileKind::Dark || roll < DEAD_SHARE {
+                    PanelState::Dead
+                } else if roll < DEAD_SHARE + FLICKERING_SHARE {
+                    PanelState::Flickering
+                } else {
+                    PanelState::Steady
+                };
+                panels.push(LightPanel { x, y, state, phase });
+            }
+        }
+        panels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::algorithms::MazeAlgorithmKind;
+    use crate::maze::topology::Topology;
+
+    #[test]
+    fn panels_are_spaced_and_mixed_the_same_for_a_seed() {
+        let maze = Maze::generate_floors(30, 30, 2, Topology::Square, 9, &MazeAlgorithmKind::default());
+        let panels = maze.place_light_panels(&mut Rng::new(12));
+        assert_eq!(panels, maze.place_light_panels(&mut Rng::new(12)));
+        assert_ne!(panels, maze.place_light_panels(&mut Rng::new(13)));
+
+        // Ten across and ten along each of the two floors.
+        assert_eq!(panels.len(), 200);
+        let offset = LIGHT_SPACING / 2;
+        for panel in &panels {
+            assert_eq!(panel.x % LIGHT_SPACING, offset, "{panel:?}");
+            assert_eq!((panel.y % maze.rows_per_floor()) % LIGHT_SPACING, offset, "{panel:?}");
+        }
+        let share = |state: PanelState| panels.iter().filter(|panel| panel.state == state).count() as f32 / 200.0;
+        assert!((share(PanelState::Dead) - DEAD_SHARE).abs() < 0.06, "{}", share(PanelState::Dead));
+        assert!((share(PanelState::Flickering) - FLICKERING_SHARE).abs() < 0.07, "{}", share(PanelState::Flickering));
+    }
+}
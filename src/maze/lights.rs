@@ -0,0 +1,106 @@
+//! Ceiling lights in play — a glowing panel and a point light per `LightPanel`,
+//! flickering harder as the clock runs down.
+
+use bevy::prelude::*;
+use crate::game_timer::GameTimer;
+use crate::maze::generator::Maze;
+use crate::maze::lighting::{LightPanel, PanelState, LIGHT_SPACING};
+use crate::maze::renderer::WALL_HEIGHT;
+use crate::maze::spatial::{cell_center, CELL_SIZE};
+use crate::rng::{derive_seed, GameRng, Rng, RngStream};
+
+/// Seed salt for panel placement, on top of the maze stream's seed.
+const LIGHTS_SALT: u64 = 6;
+
+/// Brightness of a panel shining fully, in lumens.
+pub const PANEL_INTENSITY: f32 = 150_000.0;
+
+/// How far a panel's light reaches, in world units.
+pub const PANEL_RANGE: f32 = LIGHT_SPACING as f32 * CELL_SIZE * 1.5;
+
+/// Cool white of a fluorescent tube.
+pub const PANEL_COLOR: Color = Color::srgb(0.85, 0.95, 1.0);
+
+/// How strongly a lit tube glows, as a multiple of PANEL_COLOR.
+pub const PANEL_GLOW: f32 = 2.0;
+
+/// One ceiling panel, with how brightly it shone last frame.
+#[derive(Component, Debug)]
+pub struct CeilingLight {
+    pub panel: LightPanel,
+    pub level: f32,
+}
+
+/// Hangs the seed's panels just under the ceiling. Dead panels get a dark tube and no
+/// light; every other panel gets its own material so its glow can follow its light.
+pub fn spawn_lights(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    maze: Res<Maze>,
+    rng: Res<GameRng>,
+) {
+    let mut panel_rng = Rng::new(derive_seed(rng.stream_seed(RngStream::Maze), LIGHTS_SALT));
+    let panels = maze.place_light_panels(&mut panel_rng);
+
+    let tube = meshes.add(Cuboid::new(CELL_SIZE * 0.6, 0.04, CELL_SIZE * 0.25));
+    let dead_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.2, 0.2, 0.2),
+        ..default()
+    });
+
+    let mut failing = 0;
+    for panel in panels {
+        let position = cell_center(&maze, panel.x, panel.y) + Vec3::Y * (WALL_HEIGHT - 0.02);
+        if panel.state == PanelState::Dead {
+            commands.spawn((
+                Mesh3d(tube.clone()),
+                MeshMaterial3d(dead_material.clone()),
+                Transform::from_translation(position),
+            ));
+            failing += 1;
+            continue;
+        }
+        if panel.state == PanelState::Flickering {
+            failing += 1;
+        }
+        commands.spawn((
+            CeilingLight { panel, level: 1.0 },
+            Mesh3d(tube.clone()),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: PANEL_COLOR,
+                emissive: LinearRgba::from(PANEL_COLOR) * PANEL_GLOW,
+                ..default()
+            })),
+            Transform::from_translation(position),
+            PointLight {
+                color: PANEL_COLOR,
+                intensity: PANEL_INTENSITY,
+                range: PANEL_RANGE,
+                ..default()
+            },
+        ));
+    }
+    info!("Hung ceiling lights, {} of them failing", failing);
+}
+
+/// Flickers every live panel, light and glow together.
+pub fn flicker_lights(
+    time: Res<Time>,
+    timer: Res<GameTimer>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut query: Query<(&mut CeilingLight, &mut PointLight, &MeshMaterial3d<StandardMaterial>)>,
+) {
+    let pressure = 1.0 - timer.remaining / timer.total;
+    for (mut light, mut point, material) in query.iter_mut() {
+        let level = light.panel.level(time.elapsed_secs(), pressure);
+        if level == light.level {
+            continue;
+        }
+        light.level = level;
+        point.intensity = PANEL_INTENSITY * level;
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.emissive = LinearRgba::from(PANEL_COLOR) * PANEL_GLOW * level;
+        }
+    }
+}
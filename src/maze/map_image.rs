@@ -0,0 +1,297 @@
+//! Map images — draws a maze as SVG or PNG for level review, bug reports and printed maps.
+//! Built straight from the `Maze` and `Placements` resources, so no window is needed.
+//! Floors of a multi-floor maze are laid out side by side, top floor on the left.
+
+use bevy::prelude::*;
+use crate::maze::floors::FloorLinkKind;
+use crate::maze::generator::Maze;
+use crate::maze::placement::Placements;
+use crate::maze::portals::Portal;
+use crate::maze::spatial::{cell_center, floor_footprint, wall_ends, CELL_SIZE};
+use crate::png;
+
+const BACKGROUND: [u8; 3] = [13, 13, 13];
+const WALL: [u8; 3] = [0, 153, 204];
+const START: [u8; 3] = [40, 220, 90];
+const TERMINAL: [u8; 3] = [255, 150, 0];
+const SOLUTION: [u8; 3] = [230, 60, 200];
+const ENEMY: [u8; 3] = [230, 30, 30];
+const STAIRS: [u8; 3] = [200, 160, 30];
+const LIFT: [u8; 3] = [130, 130, 160];
+const ONE_WAY: [u8; 3] = [240, 240, 240];
+const SECRET: [u8; 3] = [0, 70, 95];
+
+/// What to draw besides the walls, start and terminal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MapOptions {
+    /// Image pixels per cell.
+    pub cell_pixels: f32,
+    /// Draw the shortest route from the start to the terminal.
+    pub solution: bool,
+    /// Mark the enemy spawn cells.
+    pub enemies: bool,
+}
+
+impl Default for MapOptions {
+    fn default() -> Self {
+        MapOptions { cell_pixels: 16.0, solution: false, enemies: false }
+    }
+}
+
+/// One thing drawn on the map, in image pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Shape {
+    Line { from: Vec2, to: Vec2, width: f32, color: [u8; 3] },
+    Dot { center: Vec2, radius: f32, color: [u8; 3] },
+}
+
+/// A maze drawn as a list of shapes, ready to write out as SVG or PNG.
+#[derive(Clone, Debug)]
+pub struct MapImage {
+    width: u32,
+    height: u32,
+    shapes: Vec<Shape>,
+}
+
+impl MapImage {
+    /// Lays out the map of `maze` with the run's `placements`.
+    pub fn new(maze: &Maze, placements: &Placements, options: MapOptions) -> Self {
+        let footprint = floor_footprint(maze);
+        let margin = CELL_SIZE / 2.0;
+        let scale = options.cell_pixels / CELL_SIZE;
+        let floors = maze.floors() as f32;
+        let width = (2.0 * margin + floors * footprint.x + (floors - 1.0) * CELL_SIZE) * scale;
+        let height = (2.0 * margin + footprint.y) * scale;
+
+        // Floors sit one footprint plus a cell of gap apart along x.
+        let to_image = |point: Vec3, floor: usize| {
+            let shift = floor as f32 * (footprint.x + CELL_SIZE);
+            Vec2::new(margin + shift + point.x, margin + point.z) * scale
+        };
+        let center = |(x, y): (usize, usize)| to_image(cell_center(maze, x, y), maze.floor_of(y));
+        let line_width = (options.cell_pixels / 8.0).max(1.0);
+        let mut shapes = Vec::new();
+
+        // Walls, each shared side once, as in the 3D renderer. One-way doors and secret
+        // walls get their own colors so a reviewer can tell them from real walls.
+        let forward = maze.topology().forward_directions();
+        for y in 0..maze.height() {
+            for x in 0..maze.width() {
+                if !maze.in_mask(x, y) {
+                    continue;
+                }
+                for &direction in maze.directions() {
+                    let owned = forward.contains(&direction) || maze.step(x, y, direction).is_none();
+                    let color = if maze.is_one_way(x, y, direction) {
+                        ONE_WAY
+                    } else if maze.is_secret(x, y, direction) {
+                        SECRET
+                    } else if !maze.cell(x, y).is_open(direction) {
+                        WALL
+                    } else {
+                        continue;
+                    };
+                    if owned {
+                        let (a, b) = wall_ends(maze, x, y, direction);
+                        let floor = maze.floor_of(y);
+                        shapes.push(Shape::Line {
+                            from: to_image(a, floor),
+                            to: to_image(b, floor),
+                            width: line_width,
+                            color,
+                        });
+                    }
+                }
+            }
+        }
+
+        for door in maze.doors() {
+            let (a, b) = wall_ends(maze, door.x, door.y, door.direction);
+            let floor = maze.floor_of(door.y);
+            shapes.push(Shape::Line {
+                from: to_image(a, floor),
+                to: to_image(b, floor),
+                width: line_width * 2.0,
+                color: door.color.rgb(),
+            });
+        }
+
+        if options.solution {
+            if let Some(path) = maze.shortest_path(placements.player, placements.terminal) {
+                // Stairs, lifts and portals jump, so only steps between neighbors get a line.
+                for pair in path.windows(2) {
+                    let (a, b) = (pair[0], pair[1]);
+                    if maze.floor_of(a.1) == maze.floor_of(b.1) && maze.portal_partner(a.0, a.1) != Some(b) {
+                        shapes.push(Shape::Line {
+                            from: center(pair[0]),
+                            to: center(pair[1]),
+                            width: line_width,
+                            color: SOLUTION,
+                        });
+                    }
+                }
+            }
+        }
+
+        let dot = |cell: (usize, usize), size: f32, color: [u8; 3]| Shape::Dot {
+            center: center(cell),
+            radius: options.cell_pixels * size,
+            color,
+        };
+        for link in maze.links() {
+            let color = match link.kind {
+                FloorLinkKind::Stairs => STAIRS,
+                FloorLinkKind::Lift => LIFT,
+            };
+            shapes.push(dot((link.x, link.y), 0.25, color));
+            shapes.push(dot(link.lower(maze), 0.25, color));
+        }
+        for (index, portal) in maze.portals().iter().enumerate() {
+            shapes.push(dot(portal.a, 0.25, Portal::color(index)));
+            shapes.push(dot(portal.b, 0.25, Portal::color(index)));
+        }
+        for key in maze.keycards() {
+            shapes.push(dot((key.x, key.y), 0.2, key.color.rgb()));
+        }
+        if options.enemies {
+            for &enemy in &placements.enemies {
+                shapes.push(dot(enemy, 0.2, ENEMY));
+            }
+        }
+        shapes.push(dot(placements.player, 0.3, START));
+        shapes.push(dot(placements.terminal, 0.3, TERMINAL));
+
+        MapImage { width: width.ceil() as u32, height: height.ceil() as u32, shapes }
+    }
+
+    /// Image width in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Image height in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The map as an SVG document.
+    pub fn to_svg(&self) -> String {
+        let hex = |[r, g, b]: [u8; 3]| format!("#{r:02x}{g:02x}{b:02x}");
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+            w = self.width,
+            h = self.height,
+        );
+        svg += &format!("<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n", hex(BACKGROUND));
+        for shape in &self.shapes {
+            svg += &match *shape {
+                Shape::Line { from, to, width, color } => format!(
+                    "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\" stroke-width=\"{:.1}\" stroke-linecap=\"round\"/>\n",
+                    from.x, from.y, to.x, to.y, hex(color), width,
+                ),
+                Shape::Dot { center, radius, color } => format!(
+                    "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"{}\"/>\n",
+                    center.x, center.y, radius, hex(color),
+                ),
+            };
+        }
+        svg + "</svg>\n"
+    }
+
+    /// The map rasterized to row-major RGB pixels.
+    pub fn to_pixels(&self) -> Vec<[u8; 3]> {
+        let mut pixels = vec![BACKGROUND; (self.width * self.height) as usize];
+        for shape in &self.shapes {
+            // Paint every pixel whose center lies within reach of the shape.
+            let (low, high, color, reach): (Vec2, Vec2, [u8; 3], f32) = match *shape {
+                Shape::Line { from, to, width, color } => (from.min(to), from.max(to), color, width / 2.0),
+                Shape::Dot { center, radius, color } => (center, center, color, radius),
+            };
+            let covers = |point: Vec2| match *shape {
+                Shape::Line { from, to, .. } => distance_to_segment(point, from, to) <= reach,
+                Shape::Dot { center, .. } => point.distance(center) <= reach,
+            };
+            let x_range = (low.x - reach).floor().max(0.0) as u32..((high.x + reach).ceil() as u32).min(self.width);
+            for y in (low.y - reach).floor().max(0.0) as u32..((high.y + reach).ceil() as u32).min(self.height) {
+                for x in x_range.clone() {
+                    if covers(Vec2::new(x as f32 + 0.5, y as f32 + 0.5)) {
+                        pixels[(y * self.width + x) as usize] = color;
+                    }
+                }
+            }
+        }
+        pixels
+    }
+
+    /// The map as a PNG file.
+    pub fn to_png(&self) -> Vec<u8> {
+        png::encode_rgb(self.width, self.height, &self.to_pixels())
+    }
+}
+
+/// Shortest distance from `point` to the segment between `a` and `b`.
+fn distance_to_segment(point: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let along = b - a;
+    let t = if along == Vec2::ZERO { 0.0 } else { ((point - a).dot(along) / along.length_squared()).clamp(0.0, 1.0) };
+    point.distance(a + along * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::algorithms::MazeAlgorithmKind;
+    use crate::maze::topology::Topology;
+    use crate::rng::Rng;
+
+    /// SVG lines stroked in `color`.
+    fn lines_in(svg: &str, [r, g, b]: [u8; 3]) -> usize {
+        svg.matches(&format!("stroke=\"#{r:02x}{g:02x}{b:02x}\"")).count()
+    }
+
+    /// How many walls the 3D renderer builds for `maze`.
+    fn drawn_walls(maze: &Maze) -> usize {
+        let mut drawn = 0;
+        for y in 0..maze.height() {
+            for x in 0..maze.width() {
+                drawn += maze.directions().iter().filter(|&&d| maze.draws_wall(x, y, d)).count();
+            }
+        }
+        drawn
+    }
+
+    #[test]
+    fn svg_draws_every_wall_once() {
+        for topology in Topology::ALL {
+            let mut maze = Maze::generate_floors(9, 7, 2, topology, 11, &MazeAlgorithmKind::Prim);
+            maze.braid(0.5, &mut Rng::new(11));
+            let placements = Placements::plan(&maze, 11);
+            let svg = MapImage::new(&maze, &placements, MapOptions::default()).to_svg();
+            assert_eq!(lines_in(&svg, WALL), drawn_walls(&maze), "{topology:?}");
+        }
+    }
+
+    #[test]
+    fn one_way_and_secret_walls_are_drawn_apart() {
+        let mut maze = Maze::generate_with(12, 12, 4, &MazeAlgorithmKind::default());
+        let mut rng = Rng::new(4);
+        maze.braid(0.5, &mut rng);
+        let placements = Placements::plan(&maze, 4);
+        let one_way = maze.add_one_way_passages(3, placements.player, placements.terminal, &mut rng);
+        let secret = maze.add_secret_walls(3, &mut rng);
+        assert!(one_way > 0 && secret > 0);
+        let svg = MapImage::new(&maze, &placements, MapOptions::default()).to_svg();
+        assert_eq!(lines_in(&svg, WALL), drawn_walls(&maze));
+        assert_eq!(lines_in(&svg, ONE_WAY), one_way);
+        assert_eq!(lines_in(&svg, SECRET), secret);
+    }
+
+    #[test]
+    fn png_is_the_size_of_the_map() {
+        let maze = Maze::generate_sized(5, 4, 2);
+        let map = MapImage::new(&maze, &Placements::plan(&maze, 2), MapOptions::default());
+        let png = map.to_png();
+        assert_eq!(png[12..16], *b"IHDR");
+        assert_eq!(png[16..20], map.width().to_be_bytes());
+        assert_eq!(png[20..24], map.height().to_be_bytes());
+    }
+}
@@ -0,0 +1,220 @@
+//! Maze masks — outlines like "Y2K", a floppy disk, or a circle that a maze is carved inside.
+//!
+//! Masks are read from plain text, one character per cell: `#` or `X` is inside the
+//! mask, `.` or a space is void. Plain PBM bitmaps (`P1`, as exported by most image
+//! editors) also work, with black (1) pixels inside. Every inside cell must be
+//! reachable from every other through edge-adjacent inside cells.
+
+use std::fmt;
+
+/// Which cells of a rectangle a maze may use.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mask {
+    width: usize,
+    height: usize,
+    cells: Vec<bool>,
+}
+
+/// Why a mask could not be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MaskError {
+    /// No cell is inside the mask.
+    Empty,
+    /// A character other than `#`, `X`, `.` or a space.
+    UnexpectedChar { line: usize, column: usize, found: char },
+    /// A PBM header or pixel that could not be read.
+    BadBitmap(String),
+    /// The inside cells form more than one island.
+    Disconnected,
+}
+
+impl fmt::Display for MaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaskError::Empty => write!(f, "mask has no cells inside it"),
+            MaskError::UnexpectedChar { line, column, found } => {
+                write!(f, "line {line}, column {column}: unexpected '{found}' in mask")
+            }
+            MaskError::BadBitmap(reason) => write!(f, "bad PBM bitmap: {reason}"),
+            MaskError::Disconnected => write!(f, "mask is split into separate islands"),
+        }
+    }
+}
+
+impl std::error::Error for MaskError {}
+
+impl Mask {
+    /// Builds a mask from a row-major grid of inside/void flags, checking it is usable.
+    pub fn new(width: usize, height: usize, cells: Vec<bool>) -> Result<Self, MaskError> {
+        assert_eq!(cells.len(), width * height, "mask cells must fill the grid");
+        let mask = Mask { width, height, cells };
+        mask.validate()?;
+        Ok(mask)
+    }
+
+    /// A filled circle `diameter` cells across.
+    pub fn circle(diameter: usize) -> Self {
+        let radius = diameter as f32 / 2.0;
+        let cells = (0..diameter * diameter)
+            .map(|idx| {
+                let dx = (idx % diameter) as f32 + 0.5 - radius;
+                let dy = (idx / diameter) as f32 + 0.5 - radius;
+                dx * dx + dy * dy <= radius * radius
+            })
+            .collect();
+        Mask::new(diameter, diameter, cells).expect("a circle is one connected island")
+    }
+
+    /// Parses a text mask or a plain PBM bitmap, telling them apart by the `P1` header.
+    pub fn parse(text: &str) -> Result<Self, MaskError> {
+        if text.trim_start().starts_with("P1") {
+            Self::from_pbm(text)
+        } else {
+            Self::from_ascii(text)
+        }
+    }
+
+    /// Parses a text mask. Short lines are padded with void and blank lines at the
+    /// end are ignored.
+    pub fn from_ascii(text: &str) -> Result<Self, MaskError> {
+        let lines: Vec<&str> = text.trim_end().lines().map(str::trim_end).collect();
+        let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        let height = lines.len();
+        if width == 0 {
+            return Err(MaskError::Empty);
+        }
+
+        let mut cells = vec![false; width * height];
+        for (y, line) in lines.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                cells[y * width + x] = match c {
+                    '#' | 'X' => true,
+                    '.' | ' ' => false,
+                    found => {
+                        return Err(MaskError::UnexpectedChar { line: y + 1, column: x + 1, found })
+                    }
+                };
+            }
+        }
+        Mask::new(width, height, cells)
+    }
+
+    /// Parses a plain (ASCII, `P1`) PBM bitmap.
+    pub fn from_pbm(text: &str) -> Result<Self, MaskError> {
+        // Comments run from '#' to the end of the line; everything else is whitespace-separated.
+        let mut tokens = text
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or(""))
+            .flat_map(str::split_whitespace);
+
+        if tokens.next() != Some("P1") {
+            return Err(MaskError::BadBitmap("missing P1 header".into()));
+        }
+        let mut dimension = |name: &str| {
+            tokens
+                .next()
+                .and_then(|token| token.parse::<usize>().ok())
+                .filter(|&value| value > 0)
+                .ok_or_else(|| MaskError::BadBitmap(format!("missing or invalid {name}")))
+        };
+        let width = dimension("width")?;
+        let height = dimension("height")?;
+
+        // Pixels may be written with or without spaces between them.
+        let pixels: Vec<char> = tokens.flat_map(str::chars).collect();
+        if pixels.len() < width * height {
+            return Err(MaskError::BadBitmap(format!(
+                "expected {} pixels, found {}",
+                width * height,
+                pixels.len()
+            )));
+        }
+        let cells = pixels[..width * height]
+            .iter()
+            .map(|&pixel| match pixel {
+                '1' => Ok(true),
+                '0' => Ok(false),
+                other => Err(MaskError::BadBitmap(format!("unexpected pixel '{other}'"))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Mask::new(width, height, cells)
+    }
+
+    /// Number of columns.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Number of rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns true if (x, y) is inside the mask.
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.cells[y * self.width + x]
+    }
+
+    /// Rejects masks with no cells or with islands a maze could not join.
+    fn validate(&self) -> Result<(), MaskError> {
+        let Some(first) = self.cells.iter().position(|&inside| inside) else {
+            return Err(MaskError::Empty);
+        };
+
+        let mut seen = vec![false; self.cells.len()];
+        let mut stack = vec![first];
+        seen[first] = true;
+        while let Some(idx) = stack.pop() {
+            let (x, y) = (idx % self.width, idx / self.width);
+            let neighbors = [
+                (x > 0).then(|| idx - 1),
+                (x + 1 < self.width).then(|| idx + 1),
+                (y > 0).then(|| idx - self.width),
+                (y + 1 < self.height).then(|| idx + self.width),
+            ];
+            for next in neighbors.into_iter().flatten() {
+                if self.cells[next] && !seen[next] {
+                    seen[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+
+        if self.cells.iter().zip(&seen).any(|(&inside, &seen)| inside && !seen) {
+            return Err(MaskError::Disconnected);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::algorithms::MazeAlgorithmKind;
+    use crate::maze::generator::Maze;
+    use crate::rng::Rng;
+
+    #[test]
+    fn void_cells_are_never_carved() {
+        let ring = Mask::from_ascii("#######\n#.....#\n#.###.#\n#.#.#.#\n###.###\n").unwrap();
+        for algorithm in MazeAlgorithmKind::ALL {
+            for mask in [Mask::circle(13), ring.clone()] {
+                let mut maze = Maze::generate_masked(&mask, 8, &algorithm);
+                maze.braid(1.0, &mut Rng::new(8));
+                for y in 0..maze.height() {
+                    for x in 0..maze.width() {
+                        let passages = maze.cell(x, y).passages;
+                        if !mask.contains(x, y) {
+                            assert_eq!(passages, 0, "{} carved void ({x}, {y})", algorithm.name());
+                            continue;
+                        }
+                        for &dir in maze.directions().iter().filter(|&&dir| passages & dir != 0) {
+                            let (nx, ny) = maze.step(x, y, dir).expect("passages lead somewhere");
+                            assert!(mask.contains(nx, ny), "{} opened ({x}, {y}) into void", algorithm.name());
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
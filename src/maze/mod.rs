@@ -1,3 +1,33 @@
 //! Maze module — generation, spatial data, and rendering.
 pub mod generator;
-pub mod renderer;
\ No newline at end of file
+pub mod topology;
+pub mod algorithms;
+pub mod braid;
+pub mod floors;
+pub mod portals;
+pub mod mask;
+pub mod chunks;
+pub mod rooms;
+pub mod solver;
+pub mod flow;
+pub mod stats;
+pub mod placement;
+pub mod doors;
+pub mod passages;
+pub mod shifting;
+pub mod tiles;
+pub mod lighting;
+pub mod text;
+pub mod golden;
+pub mod batches;
+pub mod spatial;
+pub mod map_image;
+pub mod renderer;
+pub mod stairs;
+pub mod jumps;
+pub mod keycards;
+pub mod gates;
+pub mod glitch;
+pub mod hazards;
+pub mod lights;
+pub mod streaming;
@@ -0,0 +1,134 @@
+//! One-way passages and secret walls — sides that are not simply open or shut.
+//! A one-way passage is open in the cell it leads out of and shut in the one it leads
+//! into; a secret wall is an open passage drawn as a solid wall.
+//! Showing them and letting the player through in play lives in `gates`.
+
+use crate::maze::generator::{opposite, Maze};
+use crate::rng::Rng;
+
+impl Maze {
+    /// Opens the way from (x, y) in `direction` while shutting the way back.
+    pub fn carve_one_way(&mut self, x: usize, y: usize, direction: u16) {
+        let (nx, ny) = self
+            .step(x, y, direction)
+            .expect("cannot carve through the outer wall");
+        self.cell_mut(x, y).passages |= direction;
+        self.cell_mut(nx, ny).passages &= !opposite(direction);
+    }
+
+    /// Returns true if the side of (x, y) facing `direction` can be crossed one way only,
+    /// in either direction.
+    pub fn is_one_way(&self, x: usize, y: usize, direction: u16) -> bool {
+        self.step(x, y, direction)
+            .is_some_and(|(nx, ny)| self.cell(x, y).is_open(direction) != self.cell(nx, ny).is_open(opposite(direction)))
+    }
+
+    /// Turns the open passage from (x, y) in `direction` into a secret wall, on both sides.
+    pub fn hide_passage(&mut self, x: usize, y: usize, direction: u16) {
+        let (nx, ny) = self
+            .step(x, y, direction)
+            .expect("secret walls stand between two cells");
+        self.cell_mut(x, y).secret |= direction;
+        self.cell_mut(nx, ny).secret |= opposite(direction);
+    }
+
+    /// Turns a secret wall back into a plain open passage once it has been found.
+    pub fn reveal_passage(&mut self, x: usize, y: usize, direction: u16) {
+        if let Some((nx, ny)) = self.step(x, y, direction) {
+            self.cell_mut(nx, ny).secret &= !opposite(direction);
+        }
+        self.cell_mut(x, y).secret &= !direction;
+    }
+
+    /// Returns true if the side of (x, y) facing `direction` is a secret wall.
+    pub fn is_secret(&self, x: usize, y: usize, direction: u16) -> bool {
+        self.cell(x, y).secret & direction != 0
+    }
+
+    /// Returns true if a secret wall stands between two neighboring cells.
+    pub fn secret_between(&self, a: (usize, usize), b: (usize, usize)) -> bool {
+        self.directions()
+            .iter()
+            .any(|&dir| self.step(a.0, a.1, dir) == Some(b) && self.is_secret(a.0, a.1, dir))
+    }
+
+    /// Planar passages open both ways with nothing else going on: no door, no secret
+    /// wall, and not the open floor inside a room. Each is listed once, from its owner.
+    fn plain_passages(&self) -> Vec<(usize, usize, u16)> {
+        let mut passages = Vec::new();
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                for &dir in self.topology().forward_directions() {
+                    let Some(other) = self.step(x, y, dir) else { continue };
+                    let plain = self.cell(x, y).is_open(dir)
+                        && !self.is_one_way(x, y, dir)
+                        && !self.is_secret(x, y, dir)
+                        && self.door_between((x, y), other).is_none()
+                        && !self.room_at(x, y).is_some_and(|room| room.contains(other.0, other.1));
+                    if plain {
+                        passages.push((x, y, dir));
+                    }
+                }
+            }
+        }
+        passages
+    }
+
+    /// Turns up to `count` passages one-way without ever trapping the player.
+    /// A passage may only lose its way back if the far side can still walk around to the
+    /// near side without a key, or if it lies on the route from `start` to `goal`, points
+    /// toward `goal`, and `goal` can be reached from its far side without a key.
+    /// Returns how many passages were turned.
+    pub fn add_one_way_passages(
+        &mut self,
+        count: usize,
+        start: (usize, usize),
+        goal: (usize, usize),
+        rng: &mut Rng,
+    ) -> usize {
+        let mut candidates = self.plain_passages();
+        rng.shuffle(&mut candidates);
+
+        let mut placed = 0;
+        for (x, y, dir) in candidates {
+            if placed == count {
+                break;
+            }
+            let other = self.step(x, y, dir).expect("plain passages lead to a cell");
+            // Pick which way the passage runs.
+            let (from, to, direction) = if rng.next_usize(2) == 0 {
+                ((x, y), other, dir)
+            } else {
+                (other, (x, y), opposite(dir))
+            };
+            let on_route = self
+                .shortest_path(start, goal)
+                .is_some_and(|path| path.windows(2).any(|pair| pair == [from, to]));
+
+            self.carve_one_way(from.0, from.1, direction);
+            let loops_back = self.reachable_with_keys(to, &[])[self.index(from.0, from.1)];
+            let safe = loops_back || (on_route && self.can_reach(to, goal, &[]));
+            if safe && self.locks_solvable(start, goal) {
+                placed += 1;
+            } else {
+                self.carve(from.0, from.1, direction);
+            }
+        }
+
+        debug_assert!(self.locks_solvable(start, goal), "one-way passages made the maze unsolvable");
+        placed
+    }
+
+    /// Hides up to `count` random open passages behind secret walls.
+    /// Secret walls can always be walked through, so they never change what is reachable.
+    /// Returns how many walls were hidden.
+    pub fn add_secret_walls(&mut self, count: usize, rng: &mut Rng) -> usize {
+        let mut candidates = self.plain_passages();
+        rng.shuffle(&mut candidates);
+        candidates.truncate(count);
+        for &(x, y, dir) in &candidates {
+            self.hide_passage(x, y, dir);
+        }
+        candidates.len()
+    }
+}
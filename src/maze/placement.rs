@@ -0,0 +1,196 @@
+//! Rule-based placement — picks cells for the player, terminal, enemies, and pickups.
+//! Rules filter candidate cells (distance, dead ends, floors, rooms); the caller then
+//! takes the farthest candidate or a seeded random one.
+
+use crate::maze::generator::Maze;
+use crate::maze::rooms::RoomKind;
+use crate::maze::solver::DistanceMap;
+use crate::rng::{derive_seed, Rng};
+
+/// One condition a cell must meet to be chosen.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlacementRule {
+    /// At least `steps` moves away from `from`. Unreachable cells never qualify.
+    MinSteps { from: (usize, usize), steps: usize },
+    /// At most `steps` moves away from `from`.
+    MaxSteps { from: (usize, usize), steps: usize },
+    /// A cell with exactly one exit.
+    DeadEnd,
+    /// On the given floor.
+    OnFloor(usize),
+    /// Inside a room of this kind.
+    InRoom(RoomKind),
+    /// In a corridor rather than a room.
+    OutsideRooms,
+    /// None of these cells.
+    Avoid(Vec<(usize, usize)>),
+}
+
+impl Maze {
+    /// Every cell inside the mask that satisfies all `rules`, in row-major order.
+    pub fn cells_where(&self, rules: &[PlacementRule]) -> Vec<(usize, usize)> {
+        // Distance rules each need a flood fill, so run those once up front.
+        let maps: Vec<Option<DistanceMap>> = rules
+            .iter()
+            .map(|rule| match rule {
+                PlacementRule::MinSteps { from, .. } | PlacementRule::MaxSteps { from, .. } => {
+                    Some(self.distances(*from))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let mut cells = Vec::new();
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let fits = self.in_mask(x, y)
+                    && rules.iter().zip(&maps).all(|(rule, map)| self.satisfies(x, y, rule, map.as_ref()));
+                if fits {
+                    cells.push((x, y));
+                }
+            }
+        }
+        cells
+    }
+
+    /// Checks one rule for (x, y). `map` is the rule's distance map, if it has one.
+    fn satisfies(&self, x: usize, y: usize, rule: &PlacementRule, map: Option<&DistanceMap>) -> bool {
+        let steps = || map.and_then(|map| map.get(x, y));
+        match rule {
+            PlacementRule::MinSteps { steps: min, .. } => steps().is_some_and(|d| d >= *min),
+            PlacementRule::MaxSteps { steps: max, .. } => steps().is_some_and(|d| d <= *max),
+            PlacementRule::DeadEnd => self.open_neighbors(x, y).len() == 1,
+            PlacementRule::OnFloor(floor) => self.floor_of(y) == *floor,
+            PlacementRule::InRoom(kind) => self.room_at(x, y).is_some_and(|room| room.kind == *kind),
+            PlacementRule::OutsideRooms => self.room_at(x, y).is_none(),
+            PlacementRule::Avoid(cells) => !cells.contains(&(x, y)),
+        }
+    }
+
+    /// The cell matching `rules` that takes the most steps to reach from `from`.
+    /// Returns None if no reachable cell matches.
+    pub fn place_farthest(&self, from: (usize, usize), rules: &[PlacementRule]) -> Option<(usize, usize)> {
+        let map = self.distances(from);
+        self.cells_where(rules)
+            .into_iter()
+            .filter_map(|(x, y)| Some((map.get(x, y)?, (x, y))))
+            .max_by_key(|&(distance, _)| distance)
+            .map(|(_, cell)| cell)
+    }
+
+    /// Up to `count` distinct cells matching `rules`, chosen at random.
+    pub fn place_random(&self, rules: &[PlacementRule], count: usize, rng: &mut Rng) -> Vec<(usize, usize)> {
+        let mut cells = self.cells_where(rules);
+        rng.shuffle(&mut cells);
+        cells.truncate(count);
+        cells
+    }
+}
+
+/// Seed salt for placement, so picking cells never shifts the maze layout.
+const PLACEMENT_SALT: u64 = 0x0050_4C41_4345; // "PLACE"
+
+/// Enemy spawn points per run, and how far they must be from the player's start.
+pub const ENEMY_COUNT: usize = 3;
+pub const ENEMY_MIN_STEPS: usize = 10;
+
+/// Pickups per run, tucked into dead ends.
+pub const PICKUP_COUNT: usize = 3;
+
+/// Where the run's objects go. Chosen once per maze and reused when the run restarts.
+#[derive(bevy::prelude::Resource, Clone, Debug, PartialEq)]
+pub struct Placements {
+    /// Start cell: a dead end on the top floor when there is one.
+    pub player: (usize, usize),
+    /// The cell on the deepest floor farthest from the start.
+    pub terminal: (usize, usize),
+    /// Enemy spawn cells, at least ENEMY_MIN_STEPS from the start.
+    pub enemies: Vec<(usize, usize)>,
+    /// Dead ends left over for pickups. Phase 2: not spawned yet.
+    pub pickups: Vec<(usize, usize)>,
+}
+
+impl Placements {
+    /// Picks every object's cell. The same maze and seed always give the same result.
+    /// Nothing is placed on a portal, where it would be jumped away from at once.
+    pub fn plan(maze: &Maze, seed: u64) -> Self {
+        let mut rng = Rng::new(derive_seed(seed, PLACEMENT_SALT));
+        let top = PlacementRule::OnFloor(0);
+        let portals: Vec<(usize, usize)> = maze.portals().iter().flat_map(|portal| [portal.a, portal.b]).collect();
+        let off_portals = PlacementRule::Avoid(portals.clone());
+
+        let player = maze
+            .place_random(&[top.clone(), PlacementRule::DeadEnd, off_portals.clone()], 1, &mut rng)
+            .into_iter()
+            .chain(maze.place_random(&[top, off_portals], 1, &mut rng))
+            .next()
+            .unwrap_or_else(|| maze.first_cell());
+
+        let mut taken = portals;
+        taken.push(player);
+        let terminal = maze
+            .place_farthest(player, &[
+                PlacementRule::OnFloor(maze.floors() - 1),
+                PlacementRule::Avoid(taken.clone()),
+            ])
+            .unwrap_or(player);
+        taken.push(terminal);
+
+        let enemies = maze.place_random(
+            &[
+                PlacementRule::MinSteps { from: player, steps: ENEMY_MIN_STEPS },
+                PlacementRule::Avoid(taken.clone()),
+            ],
+            ENEMY_COUNT,
+            &mut rng,
+        );
+
+        taken.extend(&enemies);
+        let pickups = maze.place_random(
+            &[PlacementRule::DeadEnd, PlacementRule::Avoid(taken)],
+            PICKUP_COUNT,
+            &mut rng,
+        );
+
+        Placements { player, terminal, enemies, pickups }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::algorithms::MazeAlgorithmKind;
+    use crate::maze::topology::Topology;
+
+    #[test]
+    fn plan_puts_the_terminal_farthest_and_keeps_to_the_rules() {
+        for seed in 0..6 {
+            let maze = Maze::generate_floors(12, 8, 2, Topology::Square, seed, &MazeAlgorithmKind::default());
+            let plan = Placements::plan(&maze, seed);
+            assert_eq!(plan, Placements::plan(&maze, seed), "seed {seed}");
+
+            let (px, py) = plan.player;
+            assert_eq!(maze.floor_of(py), 0, "seed {seed}");
+            assert_eq!(maze.open_neighbors(px, py).len(), 1, "seed {seed}: start is not a dead end");
+
+            let from_player = maze.distances(plan.player);
+            let farthest = maze
+                .cells_where(&[PlacementRule::OnFloor(1)])
+                .into_iter()
+                .filter_map(|(x, y)| from_player.get(x, y))
+                .max();
+            assert_eq!(maze.floor_of(plan.terminal.1), 1, "seed {seed}");
+            assert_eq!(from_player.get(plan.terminal.0, plan.terminal.1), farthest, "seed {seed}");
+
+            assert_eq!(plan.enemies.len(), ENEMY_COUNT, "seed {seed}");
+            for &(x, y) in &plan.enemies {
+                assert!(from_player.get(x, y).is_some_and(|steps| steps >= ENEMY_MIN_STEPS), "seed {seed}");
+                assert!((x, y) != plan.terminal, "seed {seed}");
+            }
+            for &(x, y) in &plan.pickups {
+                assert_eq!(maze.open_neighbors(x, y).len(), 1, "seed {seed}: pickup outside a dead end");
+                assert!(![plan.player, plan.terminal].contains(&(x, y)) && !plan.enemies.contains(&(x, y)));
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Portals — pairs of cells joined by a network jump. Stepping into one end lands you
+//! on the other, so each pair is one more edge in the maze graph for the solver and AI.
+//! Jumping in play lives in `jumps`.
+
+use crate::maze::generator::{Maze, DOWN, UP};
+use crate::rng::Rng;
+
+/// Fewest steps the walk between the two ends of a portal may take, so every jump
+/// is worth taking.
+pub const PORTAL_MIN_STEPS: usize = 10;
+
+/// Display colors for portals as 8-bit sRGB, cycled through in placement order.
+/// Both ends of a portal share a color; the meshes and the map image use the same ones.
+pub const PORTAL_COLORS: [[u8; 3]; 4] = [[60, 255, 160], [200, 80, 255], [255, 220, 60], [60, 200, 255]];
+
+/// Two cells joined by a network jump, usable in both directions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Portal {
+    pub a: (usize, usize),
+    pub b: (usize, usize),
+}
+
+impl Portal {
+    /// Display color of the portal at `index` in the maze's portal list.
+    pub fn color(index: usize) -> [u8; 3] {
+        PORTAL_COLORS[index % PORTAL_COLORS.len()]
+    }
+
+    /// The end across from `cell`, or None if `cell` is not an end of this portal.
+    pub fn partner(&self, cell: (usize, usize)) -> Option<(usize, usize)> {
+        if cell == self.a {
+            Some(self.b)
+        } else if cell == self.b {
+            Some(self.a)
+        } else {
+            None
+        }
+    }
+}
+
+impl Maze {
+    /// Where a jump from (x, y) lands, if (x, y) is a portal.
+    pub fn portal_partner(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        self.portals().iter().find_map(|portal| portal.partner((x, y)))
+    }
+
+    /// Joins two cells with a portal.
+    pub fn link_portals(&mut self, a: (usize, usize), b: (usize, usize)) {
+        assert!(a != b, "a portal needs two different cells");
+        self.portals_mut().push(Portal { a, b });
+    }
+
+    /// Places up to `count` portals, each joining two cells at least PORTAL_MIN_STEPS
+    /// apart. Dead ends are used first, turning them into shortcuts; rooms, stairwells,
+    /// lifts and cells that already hold a portal are skipped.
+    /// Returns how many portals were placed.
+    pub fn place_portals(&mut self, count: usize, rng: &mut Rng) -> usize {
+        let mut free: Vec<(usize, usize)> = (0..self.height())
+            .flat_map(|y| (0..self.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.in_mask(x, y) && self.room_at(x, y).is_none())
+            .filter(|&(x, y)| !self.cell(x, y).is_open(UP) && !self.cell(x, y).is_open(DOWN))
+            .filter(|&(x, y)| self.portal_partner(x, y).is_none())
+            .collect();
+        rng.shuffle(&mut free);
+        // Stable, so dead ends keep their shuffled order at the front.
+        free.sort_by_key(|&(x, y)| self.open_neighbors(x, y).len() != 1);
+
+        let mut placed = 0;
+        while placed < count {
+            let Some(a) = free.first().copied() else { break };
+            free.remove(0);
+            let map = self.distances(a);
+            let far_enough = |&(x, y): &(usize, usize)| map.get(x, y).is_some_and(|steps| steps >= PORTAL_MIN_STEPS);
+            let Some(index) = free.iter().position(far_enough) else { continue };
+            let b = free.remove(index);
+            self.link_portals(a, b);
+            placed += 1;
+        }
+        placed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::algorithms::MazeAlgorithmKind;
+
+    #[test]
+    fn portal_ends_are_far_apart_and_never_shared() {
+        for seed in 0..6 {
+            let plain = Maze::generate_with(16, 16, seed, &MazeAlgorithmKind::default());
+            let mut maze = plain.clone();
+            let placed = maze.place_portals(4, &mut Rng::new(seed));
+            assert_eq!(placed, maze.portals().len(), "seed {seed}");
+            assert!(placed > 0, "seed {seed}");
+
+            let mut ends = Vec::new();
+            for portal in maze.portals() {
+                let steps = plain.distances(portal.a).get(portal.b.0, portal.b.1);
+                assert!(steps.is_some_and(|steps| steps >= PORTAL_MIN_STEPS), "seed {seed}: {portal:?}");
+                assert_eq!(maze.portal_partner(portal.a.0, portal.a.1), Some(portal.b));
+                ends.extend([portal.a, portal.b]);
+            }
+            let count = ends.len();
+            ends.sort_unstable();
+            ends.dedup();
+            assert_eq!(ends.len(), count, "seed {seed}: two portals share a cell");
+        }
+    }
+}
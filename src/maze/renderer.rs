@@ -1,37 +1,119 @@
 //! Maze renderer — builds 3D box meshes from maze cell data.
-//! Walls are Cuboid meshes with a solid color material.
+//! Walls are Cuboid boxes merged into one mesh and one compound collider per batch
+//! of cells, all sharing a solid color material.
+//! Floors of a multi-floor maze are stacked downward, FLOOR_SPACING apart, each with a
+//! ceiling slab over it at wall height.
+//! Hex and polar walls are the same boxes turned to run between the cell's corners.
+//! Void cells outside a mask get no floor and no walls.
 //! Wireframe overlay is stubbed for future use.
 
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
-use crate::maze::generator::{Maze, MAZE_WIDTH, MAZE_HEIGHT, NORTH, EAST};
+use crate::maze::batches::WallBatchKey;
+use crate::maze::floors::FloorLinkKind;
+use crate::maze::generator::{opposite, Maze};
+use crate::maze::rooms::RoomKind;
+use crate::maze::tiles::TileKind;
+use crate::maze::spatial::{cell_center, floor_elevation, floor_footprint, wall_ends, CELL_SIZE};
+use crate::maze::topology::Topology;
 
 /// Wall dimensions in world units.
 pub const WALL_HEIGHT: f32 = 2.0;
 pub const WALL_THICKNESS: f32 = 0.2;
-pub const CELL_SIZE: f32 = 2.0;
+
+/// Wall color and glow, shared with anything that has to pass for a wall.
+pub const WALL_COLOR: Color = Color::srgb(0.0, 0.6, 0.8); // cyan-blue tone
+pub const WALL_EMISSIVE: LinearRgba = LinearRgba::new(0.0, 0.3, 0.4, 1.0);
+
+/// Brightness of the ambient light that fills the maze.
+pub const AMBIENT_BRIGHTNESS: f32 = 0.3;
 
 /// Marker component for maze wall entities.
 #[derive(Component)]
 pub struct MazeWall;
 
+/// The batch of cells a merged wall entity draws, so it can be rebuilt when the maze changes.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WallBatch(pub WallBatchKey);
+
+/// The cell side a wall belongs to, so it can be found again when the maze changes.
+/// Inner walls are always stored as one of the topology's forward sides of a cell.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WallSlot {
+    pub x: usize,
+    pub y: usize,
+    pub direction: u16,
+}
+
+impl WallSlot {
+    /// The slot for the wall on `direction` side of (x, y), seen from whichever cell owns it.
+    pub fn of(maze: &Maze, x: usize, y: usize, direction: u16) -> Self {
+        let forward = maze.topology().forward_directions().contains(&direction);
+        match maze.step(x, y, direction) {
+            Some((nx, ny)) if !forward => WallSlot { x: nx, y: ny, direction: opposite(direction) },
+            _ => WallSlot { x, y, direction },
+        }
+    }
+}
+
+/// How `spawn_maze` builds walls: merged per batch of cells, or one entity each.
+/// Only `--bench-walls` asks for single walls, to measure what batching saves.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WallLayout {
+    #[default]
+    Batched,
+    Single,
+}
+
+/// Material shared by every maze wall, kept around for walls spawned mid-run.
+#[derive(Resource)]
+pub struct WallMaterial(pub Handle<StandardMaterial>);
+
 /// Marker component for maze floor entity.
 #[derive(Component)]
 pub struct MazeFloor;
 
+/// Marker component for the ceiling slabs over each floor.
+#[derive(Component)]
+pub struct MazeCeiling;
+
+/// Floor panel marking out a room, tagged with what the room is.
+#[derive(Component)]
+pub struct RoomFloor(pub RoomKind);
+
+/// Floor panel over a hazard tile, tagged with its kind.
+#[derive(Component)]
+pub struct HazardTile(pub TileKind);
+
+/// Marker component for the stairwell / lift visuals at one end of a floor link.
+#[derive(Component)]
+pub struct FloorLinkMarker;
+
+/// Where a wall on the `direction` side of (x, y) stands, and how long it is.
+/// Walls are built along x and then turned to run from one corner to the other.
+pub fn wall_placement(maze: &Maze, x: usize, y: usize, direction: u16) -> (Transform, f32) {
+    let (a, b) = wall_ends(maze, x, y, direction);
+    let along = b - a;
+    // Angled joints leave a notch at each corner, so hex and polar walls run a little long.
+    let overlap = if maze.topology() == Topology::Square { 0.0 } else { WALL_THICKNESS };
+    let transform = Transform::from_translation((a + b) / 2.0 + Vec3::Y * WALL_HEIGHT / 2.0)
+        .with_rotation(Quat::from_rotation_y(f32::atan2(-along.z, along.x)));
+    (transform, along.length() + overlap)
+}
+
 /// Spawns all maze walls and floor as 3D box meshes.
 pub fn spawn_maze(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     maze: Res<Maze>,
+    layout: Option<Res<WallLayout>>,
 ) {
-    let wall_color = Color::srgb(0.0, 0.6, 0.8); // cyan-blue tone
     let floor_color = Color::srgb(0.05, 0.05, 0.05); // near black
 
     let wall_material = materials.add(StandardMaterial {
-        base_color: wall_color,
-        emissive: LinearRgba::new(0.0, 0.3, 0.4, 1.0),
+        base_color: WALL_COLOR,
+        emissive: WALL_EMISSIVE,
         ..default()
     });
 
@@ -39,92 +121,261 @@ pub fn spawn_maze(
         base_color: floor_color,
         ..default()
     });
+    let ceiling_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.35, 0.35, 0.33), // stained acoustic tiles
+        perceptual_roughness: 1.0,
+        ..default()
+    });
+    commands.insert_resource(WallMaterial(wall_material.clone()));
 
-    // Spawn floor
-    commands.spawn((
-        MazeFloor,
-        Mesh3d(meshes.add(Cuboid::new(
-            MAZE_WIDTH as f32 * CELL_SIZE,
-            0.1,
-            MAZE_HEIGHT as f32 * CELL_SIZE,
-        ))),
-        MeshMaterial3d(floor_material),
-        Transform::from_xyz(
-            MAZE_WIDTH as f32 * CELL_SIZE / 2.0,
-            -0.05,
-            MAZE_HEIGHT as f32 * CELL_SIZE / 2.0,
-        ),
-        Collider::cuboid(
-            MAZE_WIDTH as f32 * CELL_SIZE / 2.0,
-            0.05,
-            MAZE_HEIGHT as f32 * CELL_SIZE / 2.0,
-        ),
-        RigidBody::Fixed,
-    ));
-
-    // Spawn walls
-    for y in 0..MAZE_HEIGHT {
-        for x in 0..MAZE_WIDTH {
-            let cell = maze.cells[y][x];
-            let wx = x as f32 * CELL_SIZE;
-            let wz = y as f32 * CELL_SIZE;
+    let maze_width = maze.width();
+    let maze_height = maze.height();
 
-            // North wall
-            if !cell.is_open(NORTH) {
+    if maze.topology() == Topology::Square {
+        // Floor and ceiling slabs, one per run of cells along each row so masked mazes
+        // leave void bare
+        for y in 0..maze_height {
+            let mut x = 0;
+            while x < maze_width {
+                if !maze.in_mask(x, y) {
+                    x += 1;
+                    continue;
+                }
+                let run_start = x;
+                while x < maze_width && maze.in_mask(x, y) {
+                    x += 1;
+                }
+                let length = (x - run_start) as f32 * CELL_SIZE;
+                let center = (cell_center(&maze, run_start, y) + cell_center(&maze, x - 1, y)) / 2.0;
+                let slab = meshes.add(Cuboid::new(length, 0.1, CELL_SIZE));
                 commands.spawn((
-                    MazeWall,
-                    Mesh3d(meshes.add(Cuboid::new(CELL_SIZE, WALL_HEIGHT, WALL_THICKNESS))),
-                    MeshMaterial3d(wall_material.clone()),
-                    Transform::from_xyz(wx + CELL_SIZE / 2.0, WALL_HEIGHT / 2.0, wz),
-                    Collider::cuboid(CELL_SIZE / 2.0, WALL_HEIGHT / 2.0, WALL_THICKNESS / 2.0),
+                    MazeFloor,
+                    Mesh3d(slab.clone()),
+                    MeshMaterial3d(floor_material.clone()),
+                    Transform::from_translation(center - Vec3::Y * 0.05),
+                    Collider::cuboid(length / 2.0, 0.05, CELL_SIZE / 2.0),
                     RigidBody::Fixed,
                 ));
-            }
-
-            // East wall
-            if !cell.is_open(EAST) {
                 commands.spawn((
-                    MazeWall,
-                    Mesh3d(meshes.add(Cuboid::new(WALL_THICKNESS, WALL_HEIGHT, CELL_SIZE))),
-                    MeshMaterial3d(wall_material.clone()),
-                    Transform::from_xyz(wx + CELL_SIZE, WALL_HEIGHT / 2.0, wz + CELL_SIZE / 2.0),
-                    Collider::cuboid(WALL_THICKNESS / 2.0, WALL_HEIGHT / 2.0, CELL_SIZE / 2.0),
-                    RigidBody::Fixed,
+                    MazeCeiling,
+                    Mesh3d(slab),
+                    MeshMaterial3d(ceiling_material.clone()),
+                    Transform::from_translation(center + Vec3::Y * (WALL_HEIGHT + 0.05)),
                 ));
             }
-        } // <-- cell loop ends here, border loops go BELOW
+        }
+    } else {
+        // Hex and polar cells do not tile into rectangles, so each floor gets one slab
+        // under its whole footprint, and one over it
+        let footprint = floor_footprint(&maze);
+        let slab = meshes.add(Cuboid::new(footprint.x, 0.1, footprint.y));
+        for floor in 0..maze.floors() {
+            let center = Vec3::new(footprint.x / 2.0, floor_elevation(floor), footprint.y / 2.0);
+            commands.spawn((
+                MazeFloor,
+                Mesh3d(slab.clone()),
+                MeshMaterial3d(floor_material.clone()),
+                Transform::from_translation(center - Vec3::Y * 0.05),
+                Collider::cuboid(footprint.x / 2.0, 0.05, footprint.y / 2.0),
+                RigidBody::Fixed,
+            ));
+            commands.spawn((
+                MazeCeiling,
+                Mesh3d(slab.clone()),
+                MeshMaterial3d(ceiling_material.clone()),
+                Transform::from_translation(center + Vec3::Y * (WALL_HEIGHT + 0.05)),
+            ));
+        }
     }
 
-    // South border walls
-    for x in 0..MAZE_WIDTH {
-        let wx = x as f32 * CELL_SIZE;
+    // Tint room floors by what the room is used for
+    for room in maze.rooms() {
+        let (base_color, emissive) = match room.kind {
+            RoomKind::ServerRoom => (Color::srgb(0.05, 0.08, 0.15), LinearRgba::new(0.0, 0.05, 0.2, 1.0)),
+            RoomKind::CubicleFarm => (Color::srgb(0.2, 0.2, 0.22), LinearRgba::BLACK), // grey carpet
+            RoomKind::BreakRoom => (Color::srgb(0.45, 0.4, 0.3), LinearRgba::BLACK), // beige linoleum
+        };
+        let north_west = cell_center(&maze, room.x, room.y);
+        let south_east = cell_center(&maze, room.x + room.width - 1, room.y + room.height - 1);
         commands.spawn((
-            MazeWall,
-            Mesh3d(meshes.add(Cuboid::new(CELL_SIZE, WALL_HEIGHT, WALL_THICKNESS))),
-            MeshMaterial3d(wall_material.clone()),
-            Transform::from_xyz(wx + CELL_SIZE / 2.0, WALL_HEIGHT / 2.0, MAZE_HEIGHT as f32 * CELL_SIZE),
-            Collider::cuboid(CELL_SIZE / 2.0, WALL_HEIGHT / 2.0, WALL_THICKNESS / 2.0),
-            RigidBody::Fixed,
+            RoomFloor(room.kind),
+            Mesh3d(meshes.add(Cuboid::new(
+                room.width as f32 * CELL_SIZE - WALL_THICKNESS,
+                0.02,
+                room.height as f32 * CELL_SIZE - WALL_THICKNESS,
+            ))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color,
+                emissive,
+                ..default()
+            })),
+            Transform::from_translation((north_west + south_east) / 2.0 + Vec3::Y * 0.01),
         ));
     }
 
-    // West border walls
-    for y in 0..MAZE_HEIGHT {
-        let wz = y as f32 * CELL_SIZE;
-        commands.spawn((
-            MazeWall,
-            Mesh3d(meshes.add(Cuboid::new(WALL_THICKNESS, WALL_HEIGHT, CELL_SIZE))),
-            MeshMaterial3d(wall_material.clone()),
-            Transform::from_xyz(0.0, WALL_HEIGHT / 2.0, wz + CELL_SIZE / 2.0),
-            Collider::cuboid(WALL_THICKNESS / 2.0, WALL_HEIGHT / 2.0, CELL_SIZE / 2.0),
-            RigidBody::Fixed,
-        ));
+    // Hazard tiles: a round panel on the floor, one material per kind
+    let hazard_mesh = meshes.add(Cylinder::new(CELL_SIZE * 0.4, 0.02));
+    let hazard_materials: Vec<(TileKind, Handle<StandardMaterial>)> = TileKind::HAZARDS
+        .into_iter()
+        .map(|kind| {
+            let (base_color, emissive) = match kind {
+                TileKind::Sparking => (Color::srgb(0.15, 0.1, 0.05), LinearRgba::new(1.0, 0.5, 0.0, 1.0)), // live wiring
+                TileKind::Flooded => (Color::srgb(0.05, 0.15, 0.35), LinearRgba::new(0.0, 0.05, 0.15, 1.0)), // standing water
+                TileKind::Dark => (Color::BLACK, LinearRgba::BLACK),
+                _ => (Color::srgb(0.4, 0.4, 0.4), LinearRgba::new(0.3, 0.3, 0.3, 1.0)), // static snow
+            };
+            (kind, materials.add(StandardMaterial { base_color, emissive, ..default() }))
+        })
+        .collect();
+    for y in 0..maze_height {
+        for x in 0..maze_width {
+            let tile = maze.tile(x, y);
+            let Some((_, material)) = hazard_materials.iter().find(|(kind, _)| *kind == tile) else { continue };
+            commands.spawn((
+                HazardTile(tile),
+                Mesh3d(hazard_mesh.clone()),
+                MeshMaterial3d(material.clone()),
+                Transform::from_translation(cell_center(&maze, x, y) + Vec3::Y * 0.01),
+            ));
+        }
+    }
+
+    // Spawn walls, one merged entity per batch of cells.
+    // One-way doors and secret walls are spawned by `gates`.
+    let layout = layout.map_or(WallLayout::Batched, |layout| *layout);
+    for (key, walls) in maze.wall_batches() {
+        match layout {
+            WallLayout::Batched => spawn_wall_batch(&mut commands, &mut meshes, &wall_material, &maze, key, &walls),
+            WallLayout::Single => {
+                for wall in walls {
+                    spawn_wall_batch(&mut commands, &mut meshes, &wall_material, &maze, key, &[wall]);
+                }
+            }
+        }
+    }
+
+    // Stairwells and lifts: a glowing pad at both ends of each link
+    let stairs_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.6, 0.5, 0.1),
+        emissive: LinearRgba::new(0.8, 0.6, 0.0, 1.0), // amber
+        ..default()
+    });
+    let lift_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.5, 0.5, 0.55),
+        emissive: LinearRgba::new(0.3, 0.3, 0.5, 1.0), // steel blue
+        ..default()
+    });
+    for link in maze.links() {
+        let material = match link.kind {
+            FloorLinkKind::Stairs => stairs_material.clone(),
+            FloorLinkKind::Lift => lift_material.clone(),
+        };
+        let (lx, ly) = link.lower(&maze);
+        for center in [cell_center(&maze, link.x, link.y), cell_center(&maze, lx, ly)] {
+            match link.kind {
+                // A short flight of steps rising toward the north edge of the cell
+                FloorLinkKind::Stairs => {
+                    for step in 0..3 {
+                        let rise = 0.1 * (step + 1) as f32;
+                        commands.spawn((
+                            FloorLinkMarker,
+                            Mesh3d(meshes.add(Cuboid::new(CELL_SIZE * 0.6, rise, 0.3))),
+                            MeshMaterial3d(material.clone()),
+                            Transform::from_translation(
+                                center + Vec3::new(0.0, rise / 2.0, 0.3 - 0.3 * step as f32),
+                            ),
+                        ));
+                    }
+                }
+                // A flat lift platform
+                FloorLinkKind::Lift => {
+                    commands.spawn((
+                        FloorLinkMarker,
+                        Mesh3d(meshes.add(Cuboid::new(CELL_SIZE * 0.7, 0.05, CELL_SIZE * 0.7))),
+                        MeshMaterial3d(material.clone()),
+                        Transform::from_translation(center + Vec3::Y * 0.025),
+                    ));
+                }
+            }
+        }
     }
 
     // Spawn ambient light
     commands.insert_resource(AmbientLight {
         color: Color::WHITE,
-        brightness: 0.3,
+        brightness: AMBIENT_BRIGHTNESS,
         ..default()
     });
-}
\ No newline at end of file
+}
+
+/// One mesh and one compound collider for `walls`, given as (x, y, direction), in the
+/// same space as `wall_placement`. None if there are no walls.
+pub fn wall_batch(maze: &Maze, walls: &[(usize, usize, u16)]) -> Option<(Mesh, Collider)> {
+    let mut mesh: Option<Mesh> = None;
+    let mut shapes = Vec::with_capacity(walls.len());
+    for &(x, y, direction) in walls {
+        let (transform, length) = wall_placement(maze, x, y, direction);
+        let piece = Mesh::from(Cuboid::new(length, WALL_HEIGHT, WALL_THICKNESS)).transformed_by(transform);
+        match mesh.as_mut() {
+            Some(mesh) => mesh.merge(&piece),
+            None => mesh = Some(piece),
+        }
+        shapes.push((
+            transform.translation,
+            transform.rotation,
+            Collider::cuboid(length / 2.0, WALL_HEIGHT / 2.0, WALL_THICKNESS / 2.0),
+        ));
+    }
+    Some((mesh?, Collider::compound(shapes)))
+}
+
+/// Spawns the merged walls of the batch at `key`; `walls` are the batch's walls as
+/// listed by `Maze::batch_walls`.
+pub fn spawn_wall_batch(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    material: &Handle<StandardMaterial>,
+    maze: &Maze,
+    key: WallBatchKey,
+    walls: &[(usize, usize, u16)],
+) {
+    let Some((mesh, collider)) = wall_batch(maze, walls) else { return };
+    commands.spawn((
+        MazeWall,
+        WallBatch(key),
+        Mesh3d(meshes.add(mesh)),
+        MeshMaterial3d(material.clone()),
+        Transform::IDENTITY,
+        collider,
+        RigidBody::Fixed,
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::algorithms::MazeAlgorithmKind;
+
+    #[test]
+    fn batches_merge_every_wall_into_mesh_and_collider() {
+        for topology in Topology::ALL {
+            let maze = Maze::generate_floors(9, 7, 2, topology, 3, &MazeAlgorithmKind::Prim);
+            let mut merged = 0;
+            for (key, walls) in maze.wall_batches() {
+                let (mesh, collider) = wall_batch(&maze, &walls).expect("listed batches have walls");
+                // Every cuboid brings its own 24 vertices, four per face.
+                assert_eq!(mesh.count_vertices(), 24 * walls.len(), "{topology:?} {key:?}");
+                let compound = collider.as_compound().expect("batch colliders are compound");
+                let placed: Vec<Vec3> = compound.shapes().map(|(translation, _, _)| translation).collect();
+                let expected: Vec<Vec3> = walls
+                    .iter()
+                    .map(|&(x, y, direction)| wall_placement(&maze, x, y, direction).0.translation)
+                    .collect();
+                assert_eq!(placed, expected, "{topology:?} {key:?}");
+                merged += walls.len();
+            }
+            assert_eq!(merged, maze.wall_batch_stats().walls, "{topology:?}");
+        }
+        assert!(wall_batch(&Maze::new(2, 2), &[]).is_none());
+    }
+}
@@ -0,0 +1,168 @@
+//! Rooms-and-corridors layouts — open office spaces joined by maze corridors.
+//! Rooms are rectangles with no internal walls, tagged with what kind of 1999
+//! office space they are so the renderer and spawners can dress them.
+
+use crate::maze::algorithms::{reconnect, MazeAlgorithm, MazeAlgorithmKind};
+use crate::maze::generator::Maze;
+use crate::rng::Rng;
+
+/// What a room is used for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RoomKind {
+    /// Small, cold, humming racks.
+    ServerRoom,
+    /// Wide open floor of cubicles.
+    CubicleFarm,
+    /// Coffee machine and a sad microwave.
+    BreakRoom,
+}
+
+impl RoomKind {
+    /// Every kind of room.
+    pub const ALL: [RoomKind; 3] = [RoomKind::ServerRoom, RoomKind::CubicleFarm, RoomKind::BreakRoom];
+
+    /// The name used in text mazes.
+    pub fn name(self) -> &'static str {
+        match self {
+            RoomKind::ServerRoom => "server-room",
+            RoomKind::CubicleFarm => "cubicle-farm",
+            RoomKind::BreakRoom => "break-room",
+        }
+    }
+
+    /// Looks a kind of room up by its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+/// An axis-aligned block of cells with every internal wall removed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Room {
+    /// North-west corner, in stacked maze coordinates.
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    pub kind: RoomKind,
+}
+
+impl Room {
+    /// Returns true if (x, y) lies inside the room.
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    /// Returns true if the two rooms overlap or touch within `margin` cells.
+    fn near(&self, other: &Room, margin: usize) -> bool {
+        self.x < other.x + other.width + margin
+            && other.x < self.x + self.width + margin
+            && self.y < other.y + other.height + margin
+            && other.y < self.y + self.height + margin
+    }
+
+    /// Every cell inside the room, row by row.
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (self.y..self.y + self.height)
+            .flat_map(move |y| (self.x..self.x + self.width).map(move |x| (x, y)))
+    }
+}
+
+/// Carves a maze with `corridors`, then opens up to `rooms` rectangular rooms and
+/// reconnects everything with as few doorways as possible.
+pub struct RoomsAndCorridors {
+    pub corridors: MazeAlgorithmKind,
+    pub rooms: usize,
+}
+
+/// Room size limits in cells (inclusive).
+const ROOM_MIN: usize = 2;
+const ROOM_MAX_WIDTH: usize = 5;
+const ROOM_MAX_HEIGHT: usize = 4;
+
+/// Placement tries per requested room before giving up on it.
+const PLACEMENT_ATTEMPTS: usize = 30;
+
+impl MazeAlgorithm for RoomsAndCorridors {
+    fn carve(&self, maze: &mut Maze, rng: &mut Rng) {
+        self.corridors.carve(maze, rng);
+
+        let rooms = place_rooms(maze, self.rooms, rng);
+        for room in &rooms {
+            open_room(maze, room);
+        }
+        reconnect(maze, rng);
+        maze.rooms_mut().extend(rooms);
+    }
+}
+
+/// Picks non-overlapping room rectangles, keeping a one-cell corridor between rooms.
+fn place_rooms(maze: &Maze, count: usize, rng: &mut Rng) -> Vec<Room> {
+    let mut rooms: Vec<Room> = Vec::new();
+    if maze.width() < ROOM_MIN || maze.height() < ROOM_MIN {
+        return rooms;
+    }
+
+    for _ in 0..count * PLACEMENT_ATTEMPTS {
+        if rooms.len() == count {
+            break;
+        }
+        let width = ROOM_MIN + rng.next_usize(ROOM_MAX_WIDTH.min(maze.width()) - ROOM_MIN + 1);
+        let height = ROOM_MIN + rng.next_usize(ROOM_MAX_HEIGHT.min(maze.height()) - ROOM_MIN + 1);
+        let kind = match rng.next_usize(3) {
+            0 => RoomKind::ServerRoom,
+            1 => RoomKind::CubicleFarm,
+            _ => RoomKind::BreakRoom,
+        };
+        let room = Room {
+            x: rng.next_usize(maze.width() - width + 1),
+            y: rng.next_usize(maze.height() - height + 1),
+            width,
+            height,
+            kind,
+        };
+        let inside_mask = room.cells().all(|(x, y)| maze.in_mask(x, y));
+        if inside_mask && rooms.iter().all(|other| !room.near(other, 1)) {
+            rooms.push(room);
+        }
+    }
+
+    rooms
+}
+
+/// Removes every wall inside the room and seals its outline.
+fn open_room(maze: &mut Maze, room: &Room) {
+    for (x, y) in room.cells() {
+        for &dir in maze.directions() {
+            let Some((nx, ny)) = maze.step(x, y, dir) else { continue };
+            if room.contains(nx, ny) {
+                maze.carve(x, y, dir);
+            } else {
+                maze.close(x, y, dir);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rooms_open_up_and_stay_reachable() {
+        for seed in 0..8 {
+            let algorithm = RoomsAndCorridors { corridors: MazeAlgorithmKind::default(), rooms: 4 };
+            let maze = Maze::generate_with(20, 16, seed, &algorithm);
+            assert!(!maze.rooms().is_empty(), "seed {seed}");
+            assert!(maze.is_connected(), "seed {seed}: reconnect left cells cut off");
+            for room in maze.rooms() {
+                for (x, y) in room.cells() {
+                    for &dir in maze.directions() {
+                        let inside = maze.step(x, y, dir).is_some_and(|(nx, ny)| room.contains(nx, ny));
+                        assert!(!inside || maze.cell(x, y).is_open(dir), "seed {seed}: wall inside {room:?}");
+                    }
+                }
+            }
+        }
+    }
+}
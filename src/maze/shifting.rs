@@ -0,0 +1,117 @@
+//! Runtime maze mutation — walls that open and close mid-run.
+//! Every change is checked so the goal stays reachable from where the player stands.
+
+use crate::maze::doors::KeyColor;
+use crate::maze::generator::Maze;
+use crate::rng::Rng;
+
+/// One wall opened or closed at runtime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WallChange {
+    pub x: usize,
+    pub y: usize,
+    pub direction: u16,
+    /// True if the wall was removed, false if it was put up.
+    pub open: bool,
+}
+
+impl Maze {
+    /// Opens or closes one wall, on both sides.
+    pub fn apply_change(&mut self, change: WallChange) {
+        if change.open {
+            self.carve(change.x, change.y, change.direction);
+        } else {
+            self.close(change.x, change.y, change.direction);
+        }
+    }
+
+    /// Toggles up to `count` random inner walls, about half opened and half closed.
+    /// Walls next to `player`, doors, one-way passages, secret walls, the open floor
+    /// inside rooms, and any closure that would cut `player` (holding `held`) off from
+    /// `goal` are left alone.
+    /// Returns the changes that were made, already applied to the maze.
+    pub fn shift_walls(
+        &mut self,
+        count: usize,
+        player: (usize, usize),
+        goal: (usize, usize),
+        held: &[KeyColor],
+        rng: &mut Rng,
+    ) -> Vec<WallChange> {
+        let mut slots: Vec<(usize, usize, u16)> = Vec::new();
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                for &dir in self.topology().forward_directions() {
+                    let Some(other) = self.step(x, y, dir) else { continue };
+                    let touches_player = (x, y) == player || other == player;
+                    let special = self.door_between((x, y), other).is_some()
+                        || self.is_one_way(x, y, dir)
+                        || self.is_secret(x, y, dir)
+                        || self.room_at(x, y).is_some_and(|room| room.contains(other.0, other.1));
+                    if !touches_player && !special {
+                        slots.push((x, y, dir));
+                    }
+                }
+            }
+        }
+        rng.shuffle(&mut slots);
+
+        let mut to_close = count / 2;
+        let mut to_open = count - to_close;
+        let mut changes = Vec::new();
+
+        for (x, y, direction) in slots {
+            if to_open == 0 && to_close == 0 {
+                break;
+            }
+            let open = !self.cell(x, y).is_open(direction);
+            if (open && to_open == 0) || (!open && to_close == 0) {
+                continue;
+            }
+
+            let change = WallChange { x, y, direction, open };
+            self.apply_change(change);
+            if !open && !self.can_reach(player, goal, held) {
+                self.apply_change(WallChange { open: true, ..change });
+                continue;
+            }
+
+            if open {
+                to_open -= 1;
+            } else {
+                to_close -= 1;
+            }
+            changes.push(change);
+        }
+
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::maze::algorithms::MazeAlgorithmKind;
+    use crate::maze::generator::Maze;
+    use crate::maze::rooms::RoomsAndCorridors;
+    use crate::rng::Rng;
+
+    #[test]
+    fn glitches_leave_rooms_open_and_the_goal_reachable() {
+        let algorithm = RoomsAndCorridors { corridors: MazeAlgorithmKind::Prim, rooms: 4 };
+        let mut maze = Maze::generate_with(16, 16, 8, &algorithm);
+        assert!(!maze.rooms().is_empty());
+        let (player, goal) = ((0, 0), (15, 15));
+        let mut rng = Rng::new(8);
+        for _ in 0..200 {
+            maze.shift_walls(6, player, goal, &[], &mut rng);
+            assert!(maze.can_reach(player, goal, &[]));
+            for room in maze.rooms() {
+                for (x, y) in room.cells() {
+                    for (nx, ny, dir) in maze.neighbors(x, y) {
+                        assert!(!room.contains(nx, ny) || maze.cell(x, y).is_open(dir), "wall put up inside {room:?}");
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Maze solver — shortest paths and distance maps over open passages.
+//! Hints, enemy AI, and level validation all route through here.
+
+use std::collections::VecDeque;
+use crate::maze::generator::Maze;
+
+/// Step counts from one origin cell to every other cell (a "Dijkstra map").
+#[derive(Clone, Debug)]
+pub struct DistanceMap {
+    width: usize,
+    origin: (usize, usize),
+    distances: Vec<Option<usize>>,
+    previous: Vec<Option<usize>>,
+}
+
+impl DistanceMap {
+    /// The cell distances were measured from.
+    pub fn origin(&self) -> (usize, usize) {
+        self.origin
+    }
+
+    /// Steps from the origin to (x, y), or None if it cannot be reached.
+    pub fn get(&self, x: usize, y: usize) -> Option<usize> {
+        self.distances[y * self.width + x]
+    }
+
+    /// Number of cells reachable from the origin, including the origin itself.
+    pub fn reachable_count(&self) -> usize {
+        self.distances.iter().filter(|d| d.is_some()).count()
+    }
+
+    /// The reachable cell farthest from the origin and its distance.
+    /// Ties go to the first cell in row-major order.
+    pub fn farthest(&self) -> ((usize, usize), usize) {
+        let mut best = (self.origin, 0);
+        for (idx, distance) in self.distances.iter().enumerate() {
+            if let Some(distance) = *distance {
+                if distance > best.1 {
+                    best = ((idx % self.width, idx / self.width), distance);
+                }
+            }
+        }
+        best
+    }
+
+    /// Cells from the origin to `to` inclusive, or None if `to` cannot be reached.
+    pub fn path_to(&self, to: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+        let mut idx = to.1 * self.width + to.0;
+        self.distances[idx]?;
+
+        let mut path = vec![to];
+        while let Some(prev) = self.previous[idx] {
+            path.push((prev % self.width, prev / self.width));
+            idx = prev;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+impl Maze {
+    /// Breadth-first flood from `from`, recording the distance to every reachable cell.
+    pub fn distances(&self, from: (usize, usize)) -> DistanceMap {
+        let total = self.width() * self.height();
+        let mut distances = vec![None; total];
+        let mut previous = vec![None; total];
+        let mut queue = VecDeque::new();
+
+        distances[self.index(from.0, from.1)] = Some(0);
+        queue.push_back(from);
+
+        while let Some((x, y)) = queue.pop_front() {
+            let idx = self.index(x, y);
+            let next_distance = distances[idx].map(|d: usize| d + 1);
+            for (nx, ny) in self.open_neighbors(x, y) {
+                let nidx = self.index(nx, ny);
+                if distances[nidx].is_none() {
+                    distances[nidx] = next_distance;
+                    previous[nidx] = Some(idx);
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+
+        DistanceMap {
+            width: self.width(),
+            origin: from,
+            distances,
+            previous,
+        }
+    }
+
+    /// Shortest route from `from` to `to` as a list of cells (both ends included),
+    /// or None if the two cells are not connected.
+    pub fn shortest_path(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Option<Vec<(usize, usize)>> {
+        self.distances(from).path_to(to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::algorithms::MazeAlgorithmKind;
+    use crate::rng::Rng;
+
+    #[test]
+    fn paths_are_as_long_as_the_distances() {
+        // Braided, so there are loops and longer ways round to pass over.
+        let mut maze = Maze::generate_with(15, 12, 8, &MazeAlgorithmKind::Prim);
+        maze.braid(0.7, &mut Rng::new(2));
+        let from = (3, 4);
+        let map = maze.distances(from);
+        assert_eq!(map.reachable_count(), 15 * 12);
+        for to in [(3, 4), (0, 0), (14, 11), (7, 0), (14, 5)] {
+            let path = maze.shortest_path(from, to).expect("every cell is reachable");
+            assert_eq!((path[0], path[path.len() - 1]), (from, to));
+            assert_eq!(Some(path.len() - 1), map.get(to.0, to.1), "{to:?}");
+            assert_eq!(maze.distances(to).get(from.0, from.1), map.get(to.0, to.1), "{to:?}");
+            for step in path.windows(2) {
+                assert!(maze.open_neighbors(step[0].0, step[0].1).contains(&step[1]), "{to:?}");
+            }
+        }
+    }
+}
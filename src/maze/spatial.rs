@@ -0,0 +1,278 @@
+//! Spatial queries — the one place world positions and maze cells are converted.
+//! Cell centers, wall corners and position lookups follow the maze's topology, and
+//! line of sight walks the grid so it agrees with the walls the player bumps into.
+
+use bevy::prelude::*;
+use std::f32::consts::TAU;
+use crate::maze::generator::{Maze, NORTH, SOUTH, EAST, WEST, NORTH_EAST, SOUTH_EAST, SOUTH_WEST};
+use crate::maze::renderer::WALL_HEIGHT;
+use crate::maze::topology::Topology;
+
+/// Width of one cell in world units.
+pub const CELL_SIZE: f32 = 2.0;
+
+/// Vertical distance between one floor and the next one down.
+pub const FLOOR_SPACING: f32 = WALL_HEIGHT + 1.0;
+
+/// Radius of the open hub in the middle of a polar maze.
+pub const POLAR_HUB_RADIUS: f32 = 2.0 * CELL_SIZE;
+
+/// Center-to-corner distance of a hex cell, so that hexes are CELL_SIZE across their flats.
+const HEX_RADIUS: f32 = CELL_SIZE / 1.732_050_8;
+
+/// World position of the floor surface at the center of cell (x, y).
+pub fn cell_center(maze: &Maze, x: usize, y: usize) -> Vec3 {
+    let (x, row) = (x as f32, maze.row_on_floor(y) as f32);
+    let center = match maze.topology() {
+        Topology::Square => Vec2::new(x + 0.5, row + 0.5) * CELL_SIZE,
+        Topology::Hex => hex_center(x, row),
+        Topology::Polar => {
+            // Ring walls are straight chords, so the middle of a cell sits a little inside
+            // the ring's middle radius; this is the inverse of the measure in `cell_at`.
+            let radius = POLAR_HUB_RADIUS + (row + 0.5) * CELL_SIZE;
+            polar_point(maze, radius * polar_chord_ratio(maze), x + 0.5)
+        }
+    };
+    Vec3::new(center.x, floor_elevation(maze.floor_of(y)), center.y)
+}
+
+/// Center of the hex at column `x` of `row`, on the xz plane. Odd rows sit half a cell east.
+fn hex_center(x: f32, row: f32) -> Vec2 {
+    let shift = if row.rem_euclid(2.0) == 1.0 { 0.5 } else { 0.0 };
+    Vec2::new((x + 0.5 + shift) * CELL_SIZE, HEX_RADIUS * (1.0 + 1.5 * row))
+}
+
+/// The middle of a polar maze on the xz plane; the whole disc sits in positive x and z.
+fn polar_origin(maze: &Maze) -> Vec2 {
+    Vec2::splat(POLAR_HUB_RADIUS + maze.rows_per_floor() as f32 * CELL_SIZE)
+}
+
+/// How far out the middle of a ring wall sits, as a share of the radius of its ends.
+fn polar_chord_ratio(maze: &Maze) -> f32 {
+    (TAU / maze.width() as f32 / 2.0).cos()
+}
+
+/// The point `radius` out from the middle of a polar maze, `sector` sectors clockwise from east.
+fn polar_point(maze: &Maze, radius: f32, sector: f32) -> Vec2 {
+    polar_origin(maze) + Vec2::from_angle(sector / maze.width() as f32 * TAU) * radius
+}
+
+/// The two corners bounding the `direction` side of cell (x, y), at floor height.
+pub fn wall_ends(maze: &Maze, x: usize, y: usize, direction: u16) -> (Vec3, Vec3) {
+    let (xf, row) = (x as f32, maze.row_on_floor(y) as f32);
+    let (a, b) = match maze.topology() {
+        Topology::Square => {
+            let corner = |dx: f32, dz: f32| Vec2::new(xf + dx, row + dz) * CELL_SIZE;
+            match direction {
+                NORTH => (corner(0.0, 0.0), corner(1.0, 0.0)),
+                SOUTH => (corner(0.0, 1.0), corner(1.0, 1.0)),
+                EAST => (corner(1.0, 0.0), corner(1.0, 1.0)),
+                _ => (corner(0.0, 0.0), corner(0.0, 1.0)),
+            }
+        }
+        Topology::Hex => {
+            // Corner 0 is the northern tip; the rest follow clockwise, 60 degrees apart
+            let center = hex_center(xf, row);
+            let corner = |k: f32| center + Vec2::from_angle((60.0 * k - 90.0).to_radians()) * HEX_RADIUS;
+            let k = match direction {
+                NORTH_EAST => 0.0,
+                EAST => 1.0,
+                SOUTH_EAST => 2.0,
+                SOUTH_WEST => 3.0,
+                WEST => 4.0,
+                _ => 5.0,
+            };
+            (corner(k), corner(k + 1.0))
+        }
+        Topology::Polar => {
+            let inner = POLAR_HUB_RADIUS + row * CELL_SIZE;
+            let outer = inner + CELL_SIZE;
+            let point = |radius: f32, sector: f32| polar_point(maze, radius, sector);
+            match direction {
+                NORTH => (point(inner, xf), point(inner, xf + 1.0)),
+                SOUTH => (point(outer, xf), point(outer, xf + 1.0)),
+                EAST => (point(inner, xf + 1.0), point(outer, xf + 1.0)),
+                _ => (point(inner, xf), point(outer, xf)),
+            }
+        }
+    };
+    let base_y = floor_elevation(maze.floor_of(y));
+    (Vec3::new(a.x, base_y, a.y), Vec3::new(b.x, base_y, b.y))
+}
+
+/// Height of the walking surface of `floor`. Floor 0 sits at 0 and deeper floors go down.
+pub fn floor_elevation(floor: usize) -> f32 {
+    -(floor as f32) * FLOOR_SPACING
+}
+
+/// The cell whose floor space contains `position`, or None if it is outside the maze.
+pub fn cell_at(maze: &Maze, position: Vec3) -> Option<(usize, usize)> {
+    let floor = (-position.y / FLOOR_SPACING + 0.5).floor();
+    let (x, row) = match maze.topology() {
+        Topology::Square => ((position.x / CELL_SIZE).floor(), (position.z / CELL_SIZE).floor()),
+        Topology::Hex => {
+            // Each hex holds the points nearer its center than any other, so compare the
+            // centers of the few cells around the nearest row.
+            let point = Vec2::new(position.x, position.z);
+            let near_row = ((position.z / HEX_RADIUS - 1.0) / 1.5).round();
+            [near_row - 1.0, near_row, near_row + 1.0]
+                .into_iter()
+                .map(|row| {
+                    let shift = if row.rem_euclid(2.0) == 1.0 { 0.5 } else { 0.0 };
+                    ((position.x / CELL_SIZE - 0.5 - shift).round(), row)
+                })
+                .min_by(|&a, &b| {
+                    let da = hex_center(a.0, a.1).distance_squared(point);
+                    let db = hex_center(b.0, b.1).distance_squared(point);
+                    da.total_cmp(&db)
+                })
+                .expect("three candidate rows")
+        }
+        Topology::Polar => {
+            let offset = Vec2::new(position.x, position.z) - polar_origin(maze);
+            let sectors = maze.width() as f32;
+            let sector = (offset.to_angle().rem_euclid(TAU) / TAU * sectors).floor().min(sectors - 1.0);
+            // Ring walls are straight chords, so measure outward along the sector's middle.
+            let middle = Vec2::from_angle((sector + 0.5) / sectors * TAU);
+            let radius = offset.dot(middle) / polar_chord_ratio(maze);
+            (sector, ((radius - POLAR_HUB_RADIUS) / CELL_SIZE).floor())
+        }
+    };
+    if floor < 0.0 || x < 0.0 || row < 0.0 {
+        return None;
+    }
+    let (floor, x, row) = (floor as usize, x as usize, row as usize);
+    if floor >= maze.floors() || x >= maze.width() || row >= maze.rows_per_floor() {
+        return None;
+    }
+    Some((x, floor * maze.rows_per_floor() + row))
+}
+
+/// Size of one floor on the xz plane, measured from the world origin.
+pub fn floor_footprint(maze: &Maze) -> Vec2 {
+    let (width, rows) = (maze.width() as f32, maze.rows_per_floor() as f32);
+    match maze.topology() {
+        Topology::Square => Vec2::new(width, rows) * CELL_SIZE,
+        Topology::Hex => Vec2::new((width + 0.5) * CELL_SIZE, HEX_RADIUS * (2.0 + 1.5 * (rows - 1.0))),
+        Topology::Polar => polar_origin(maze) * 2.0,
+    }
+}
+
+/// The cells reachable in one move from (x, y), with their centers.
+/// Includes stairwells, lifts and portals, so the center may be on another floor or
+/// across the maze.
+pub fn walkable_neighbors(maze: &Maze, x: usize, y: usize) -> impl Iterator<Item = ((usize, usize), Vec3)> + '_ {
+    maze.open_neighbors(x, y)
+        .into_iter()
+        .map(|(nx, ny)| ((nx, ny), cell_center(maze, nx, ny)))
+}
+
+/// Distance between samples when walking a sight line: an eighth of a cell.
+const SIGHT_STEP: f32 = CELL_SIZE / 8.0;
+
+/// Returns true if nothing but open passages lies between `from` and `to`.
+/// The line is walked across the floor of `from` cell by cell, and every crossing into a
+/// new cell must go through an open side. Both ends must be on the same floor.
+/// Locked doors, one-way doors and secret walls look solid from both sides, so they
+/// block sight too.
+pub fn line_of_sight(maze: &Maze, from: Vec3, to: Vec3) -> bool {
+    let (Some(start), Some(end)) = (cell_at(maze, from), cell_at(maze, to)) else {
+        return false;
+    };
+    if maze.floor_of(start.1) != maze.floor_of(end.1) {
+        return false;
+    }
+
+    let flat_to = Vec3::new(to.x, from.y, to.z);
+    let samples = (from.distance(flat_to) / SIGHT_STEP).ceil() as usize;
+    let mut current = start;
+    for i in 1..=samples {
+        let point = from.lerp(flat_to, i as f32 / samples as f32);
+        let Some(next) = cell_at(maze, point) else { return false };
+        if next == current {
+            continue;
+        }
+        // Sight only passes through real sides, never along stairs, lifts or portals.
+        let side_open = |a: (usize, usize), b: (usize, usize)| {
+            maze.neighbors(a.0, a.1)
+                .into_iter()
+                .any(|(nx, ny, dir)| (nx, ny) == b && maze.cell(a.0, a.1).is_open(dir))
+        };
+        let open = |a: (usize, usize), b: (usize, usize)| {
+            side_open(a, b) && side_open(b, a) && !maze.secret_between(a, b) && maze.door_between(a, b).is_none()
+        };
+        // Grazing a corner can skip a cell, so a jump to a cell that is not a neighbor
+        // counts as clear if some open route goes around the corner.
+        let adjacent = maze.neighbors(current.0, current.1).iter().any(|&(nx, ny, _)| (nx, ny) == next);
+        let clear = if adjacent {
+            open(current, next)
+        } else {
+            maze.open_neighbors(current.0, current.1).into_iter().any(|via| open(current, via) && open(via, next))
+        };
+        if !clear {
+            return false;
+        }
+        current = next;
+    }
+    true
+}
+
+/// Line of sight between the centers of two cells.
+pub fn cells_see_each_other(maze: &Maze, a: (usize, usize), b: (usize, usize)) -> bool {
+    line_of_sight(maze, cell_center(maze, a.0, a.1), cell_center(maze, b.0, b.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::doors::{Door, KeyColor};
+
+    #[test]
+    fn cell_centers_map_back_to_their_cells() {
+        for topology in Topology::ALL {
+            let maze = Maze::new_shaped(9, 6, 2, topology);
+            for y in 0..maze.height() {
+                for x in 0..maze.width() {
+                    let center = cell_center(&maze, x, y);
+                    assert_eq!(cell_at(&maze, center), Some((x, y)), "{topology:?} ({x}, {y})");
+                    // Anywhere well inside the cell, and at head height, is still that cell.
+                    for nudge in [Vec3::X, Vec3::NEG_X, Vec3::Z, Vec3::NEG_Z] {
+                        let point = center + nudge * CELL_SIZE * 0.2 + Vec3::Y * 1.5;
+                        assert_eq!(cell_at(&maze, point), Some((x, y)), "{topology:?} ({x}, {y}) {nudge}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn positions_off_the_maze_have_no_cell() {
+        let maze = Maze::new(4, 4);
+        assert_eq!(cell_at(&maze, Vec3::new(-0.5, 0.0, 1.0)), None);
+        assert_eq!(cell_at(&maze, Vec3::new(1.0, 0.0, 4.0 * CELL_SIZE + 0.1)), None);
+        assert_eq!(cell_at(&maze, Vec3::new(1.0, -FLOOR_SPACING, 1.0)), None);
+    }
+
+    #[test]
+    fn sight_passes_open_sides_only() {
+        let mut maze = Maze::new(3, 1);
+        maze.carve(0, 0, EAST);
+        assert!(cells_see_each_other(&maze, (0, 0), (1, 0)));
+        assert!(!cells_see_each_other(&maze, (1, 0), (2, 0)));
+        assert!(!cells_see_each_other(&maze, (0, 0), (2, 0)));
+
+        maze.doors_mut().push(Door { x: 0, y: 0, direction: EAST, color: KeyColor::Red });
+        assert!(!cells_see_each_other(&maze, (0, 0), (1, 0)), "locked doors block sight");
+    }
+
+    #[test]
+    fn grazing_a_corner_needs_a_way_around_it() {
+        // The diagonal between (0, 0) and (1, 1) runs exactly through the shared corner.
+        let mut maze = Maze::new(2, 2);
+        maze.carve(0, 0, SOUTH);
+        assert!(!cells_see_each_other(&maze, (0, 0), (1, 1)));
+        maze.carve(0, 0, EAST);
+        maze.carve(1, 0, SOUTH);
+        assert!(cells_see_each_other(&maze, (0, 0), (1, 1)));
+    }
+}
@@ -0,0 +1,45 @@
+//! Floor links in play — press E on a stairwell or lift to change floors.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use crate::maze::generator::Maze;
+use crate::maze::renderer::WALL_HEIGHT;
+use crate::maze::spatial::cell_center;
+use crate::player::controller::Player;
+
+/// How close to the middle of a link's cell the player must stand to use it.
+pub const LINK_RANGE: f32 = 0.8;
+
+/// Moves the player to the other end of a stairwell or lift when E is pressed on it.
+pub fn use_floor_links(
+    keys: Res<ButtonInput<KeyCode>>,
+    maze: Res<Maze>,
+    mut player_query: Query<(&mut Transform, &mut Velocity), With<Player>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyE) { return; }
+    let Ok((mut transform, mut velocity)) = player_query.get_single_mut() else { return; };
+
+    let standing_on = |center: Vec3| {
+        (transform.translation - center).xz().length() <= LINK_RANGE
+            && (transform.translation.y - center.y).abs() <= WALL_HEIGHT
+    };
+
+    for link in maze.links() {
+        let (lx, ly) = link.lower(&maze);
+        let upper = cell_center(&maze, link.x, link.y);
+        let lower = cell_center(&maze, lx, ly);
+
+        let (target, floor) = if standing_on(upper) {
+            (lower, maze.floor_of(ly))
+        } else if standing_on(lower) {
+            (upper, maze.floor_of(link.y))
+        } else {
+            continue;
+        };
+
+        transform.translation = target + Vec3::Y * 0.5;
+        *velocity = Velocity::zero();
+        info!("Took the {:?} to floor {}", link.kind, floor);
+        return;
+    }
+}
@@ -0,0 +1,265 @@
+//! Maze metrics and difficulty scoring.
+//! Numbers for tuning: dead ends, path lengths, branching, and corridor runs,
+//! folded into one difficulty score that generation can reroll against.
+
+use crate::maze::generator::{opposite, Maze};
+
+/// Layout measurements of one maze between a start and a goal cell.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MazeStats {
+    /// Total number of cells.
+    pub cells: usize,
+    /// Cells with exactly one exit.
+    pub dead_ends: usize,
+    /// Cells with three or more exits.
+    pub junctions: usize,
+    /// Longest shortest path between any two cells (the maze's diameter), in steps.
+    pub longest_path: usize,
+    /// Steps on the shortest route from start to goal.
+    pub solution_length: usize,
+    /// Average number of wrong turns offered at each step of the solution.
+    pub branching_factor: f32,
+    /// `corridor_runs[n]` is how many straight corridors are exactly n cells long.
+    pub corridor_runs: Vec<usize>,
+}
+
+impl MazeStats {
+    /// Measures `maze` for a run that starts at `start` and ends at `goal`.
+    pub fn analyze(maze: &Maze, start: (usize, usize), goal: (usize, usize)) -> Self {
+        let mut dead_ends = 0;
+        let mut junctions = 0;
+        for y in 0..maze.height() {
+            for x in 0..maze.width() {
+                match maze.open_neighbors(x, y).len() {
+                    1 => dead_ends += 1,
+                    n if n >= 3 => junctions += 1,
+                    _ => {}
+                }
+            }
+        }
+
+        let from_start = maze.distances(start);
+        let solution = from_start.path_to(goal).unwrap_or_default();
+        let solution_length = solution.len().saturating_sub(1);
+
+        // Wrong turns: exits at each step other than the one we came from and the one we take.
+        let wrong_turns: usize = solution
+            .iter()
+            .enumerate()
+            .map(|(i, &(x, y))| {
+                let used = usize::from(i > 0) + usize::from(i + 1 < solution.len());
+                maze.open_neighbors(x, y).len().saturating_sub(used)
+            })
+            .sum();
+        let branching_factor = if solution.is_empty() {
+            0.0
+        } else {
+            wrong_turns as f32 / solution.len() as f32
+        };
+
+        MazeStats {
+            cells: maze.cell_count(),
+            dead_ends,
+            junctions,
+            longest_path: longest_path(maze, start),
+            solution_length,
+            branching_factor,
+            corridor_runs: corridor_runs(maze),
+        }
+    }
+
+    /// Single difficulty number, roughly 0 (trivial) to 100 (brutal).
+    /// Weighs how much of the maze the solution winds through, how many wrong
+    /// turns it offers, and how many dead ends there are to get lost in.
+    pub fn difficulty(&self) -> f32 {
+        if self.cells == 0 {
+            return 0.0;
+        }
+        let winding = self.solution_length as f32 / self.cells as f32;
+        let dead_end_ratio = self.dead_ends as f32 / self.cells as f32;
+        (winding * 120.0 + self.branching_factor * 40.0 + dead_end_ratio * 60.0).min(100.0)
+    }
+}
+
+/// Diameter estimate by double sweep: the farthest cell from `start`, then the farthest
+/// cell from that. Exact for perfect mazes, a close lower bound once loops are added.
+fn longest_path(maze: &Maze, start: (usize, usize)) -> usize {
+    let (far, _) = maze.distances(start).farthest();
+    let (_, distance) = maze.distances(far).farthest();
+    distance
+}
+
+/// Histogram of straight corridor lengths along each of the topology's forward sides:
+/// east-west and north-south runs on square cells, the three axes of a hex grid, and
+/// around the rings and out along the spokes of a polar maze.
+fn corridor_runs(maze: &Maze) -> Vec<usize> {
+    let longest = maze.width().max(maze.height());
+    let mut histogram = vec![0; longest + 1];
+
+    for &direction in maze.topology().forward_directions() {
+        for y in 0..maze.height() {
+            for x in 0..maze.width() {
+                // Count each run once, from the cell it is not entered from going this way.
+                let entered = maze
+                    .step(x, y, opposite(direction))
+                    .is_some_and(|(px, py)| maze.cell(px, py).is_open(direction));
+                if entered {
+                    continue;
+                }
+                let (mut cell, mut run) = ((x, y), 1);
+                while run < longest && maze.cell(cell.0, cell.1).is_open(direction) {
+                    let Some(next) = maze.step(cell.0, cell.1, direction) else { break };
+                    cell = next;
+                    run += 1;
+                }
+                if run >= 2 {
+                    histogram[run] += 1;
+                }
+            }
+        }
+    }
+
+    histogram
+}
+
+/// Named difficulty targets, each a band of `MazeStats::difficulty` scores.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    /// Score range a maze must land in to count as this difficulty.
+    pub fn band(&self) -> std::ops::Range<f32> {
+        match self {
+            Difficulty::Easy => 0.0..40.0,
+            Difficulty::Normal => 40.0..60.0,
+            Difficulty::Hard => 60.0..101.0,
+        }
+    }
+
+    /// Looks up a difficulty by its lowercase name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "easy" => Some(Difficulty::Easy),
+            "normal" => Some(Difficulty::Normal),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+}
+
+/// How many seeds to try before settling for the closest miss.
+pub const MAX_REROLLS: u64 = 64;
+
+/// Calls `generate` with `seed`, `seed + 1`, ... until `score` lands inside `band`.
+/// The same base seed always settles on the same maze. If no attempt hits the band,
+/// the attempt closest to it is returned.
+pub fn generate_in_band(
+    seed: u64,
+    band: std::ops::Range<f32>,
+    mut generate: impl FnMut(u64) -> Maze,
+    score: impl Fn(&Maze) -> f32,
+) -> Maze {
+    let mut best: Option<(f32, Maze)> = None;
+
+    for attempt in 0..MAX_REROLLS {
+        let maze = generate(seed.wrapping_add(attempt));
+        let difficulty = score(&maze);
+        if band.contains(&difficulty) {
+            return maze;
+        }
+        let miss = if difficulty < band.start {
+            band.start - difficulty
+        } else {
+            difficulty - band.end
+        };
+        if best.as_ref().is_none_or(|(best_miss, _)| miss < *best_miss) {
+            best = Some((miss, maze));
+        }
+    }
+
+    best.map(|(_, maze)| maze).expect("at least one attempt was made")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::generator::{EAST, SOUTH, SOUTH_EAST};
+    use crate::maze::topology::Topology;
+
+    #[test]
+    fn corridor_runs_follow_straight_passages() {
+        // A 4-cell corridor along the top, and a 3-cell one down the left side.
+        let mut maze = Maze::new(4, 3);
+        for x in 0..3 {
+            maze.carve(x, 0, EAST);
+        }
+        maze.carve(0, 0, SOUTH);
+        maze.carve(0, 1, SOUTH);
+        let runs = corridor_runs(&maze);
+        assert_eq!((runs[2], runs[3], runs[4]), (0, 1, 1));
+        assert_eq!(runs.iter().sum::<usize>(), 2);
+    }
+
+    #[test]
+    fn hex_and_polar_runs_follow_their_own_axes() {
+        // A diagonal down a hex grid.
+        let mut hex = Maze::new_shaped(4, 4, 1, Topology::Hex);
+        let mut cell = (0, 0);
+        for _ in 0..3 {
+            hex.carve(cell.0, cell.1, SOUTH_EAST);
+            cell = hex.step(cell.0, cell.1, SOUTH_EAST).unwrap();
+        }
+        assert_eq!(corridor_runs(&hex)[4], 1);
+
+        // All the way round a ring but for one wall, and a ring with no wall at all.
+        let mut polar = Maze::new_shaped(6, 2, 1, Topology::Polar);
+        for x in 0..5 {
+            polar.carve(x, 0, EAST);
+        }
+        for x in 0..6 {
+            polar.carve(x, 1, EAST);
+        }
+        let runs = corridor_runs(&polar);
+        assert_eq!(runs[6], 1);
+        assert_eq!(runs.iter().sum::<usize>(), 1);
+    }
+
+    #[test]
+    fn rerolls_settle_on_the_first_maze_in_the_band() {
+        let score = |maze: &Maze| {
+            let start = (0, 0);
+            let (goal, _) = maze.distances(start).farthest();
+            MazeStats::analyze(maze, start, goal).difficulty()
+        };
+        let scores: Vec<f32> = (0..MAX_REROLLS).map(|seed| score(&Maze::generate_sized(8, 8, 100 + seed))).collect();
+        // A band that only the sixth attempt's score falls in.
+        let target = scores[5];
+        let band = target..target + f32::EPSILON * 64.0;
+        assert!(scores[..5].iter().all(|score| !band.contains(score)), "pick another base seed");
+
+        let mut attempts = 0;
+        let maze = generate_in_band(100, band.clone(), |seed| {
+            attempts += 1;
+            Maze::generate_sized(8, 8, seed)
+        }, score);
+        assert_eq!(attempts, 6);
+        assert!(band.contains(&score(&maze)));
+        assert_eq!(maze, Maze::generate_sized(8, 8, 105));
+    }
+
+    #[test]
+    fn rerolls_fall_back_to_the_closest_miss() {
+        let score = |maze: &Maze| maze.dead_ends().len() as f32;
+        let closest = (0..MAX_REROLLS)
+            .map(|seed| Maze::generate_sized(8, 8, seed))
+            .max_by(|a, b| score(a).total_cmp(&score(b)))
+            .unwrap();
+        let maze = generate_in_band(0, 1000.0..2000.0, |seed| Maze::generate_sized(8, 8, seed), score);
+        assert_eq!(score(&maze), score(&closest));
+    }
+}
@@ -0,0 +1,177 @@
+//! Endless mode in play — chunks are built around the player as they walk, and chunks
+//! left far behind are despawned, walls, colliders and all, so the entity count stays flat.
+//! Every newly reached chunk puts some time back on the clock.
+//! The `Maze` resource only holds the starting chunk, so systems that look cells up in it
+//! (hazards, doors, gates, portals, glitches and enemies) do not run in endless mode.
+
+use std::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use crate::game_timer::GameTimer;
+use crate::maze::algorithms::MazeAlgorithmKind;
+use crate::maze::chunks::{chunk_at, chunk_origin, ChunkCoord, CHUNK_SIZE};
+use crate::maze::generator::{Maze, EAST, SOUTH};
+use crate::maze::renderer::{wall_batch, MazeWall, AMBIENT_BRIGHTNESS, WALL_COLOR, WALL_EMISSIVE};
+use crate::maze::spatial::CELL_SIZE;
+use crate::player::controller::Player;
+
+/// Chunks kept built in every direction around the player's chunk.
+pub const LOAD_RADIUS: i32 = 1;
+
+/// Chunks farther than this from the player's chunk are despawned. Kept above
+/// LOAD_RADIUS so pacing back and forth over a border does not rebuild chunks.
+pub const UNLOAD_RADIUS: i32 = 2;
+
+/// Seconds added to the clock, up to its full length, for each chunk reached first time.
+pub const CHUNK_TIME_BONUS: f32 = 10.0;
+
+/// The endless maze being played, present only in endless mode.
+#[derive(Resource, Debug)]
+pub struct EndlessMaze {
+    pub seed: u64,
+    pub algorithm: MazeAlgorithmKind,
+    /// Root entity of every chunk currently built.
+    pub loaded: HashMap<ChunkCoord, Entity>,
+    /// Every chunk the player has stood in.
+    pub reached: HashSet<ChunkCoord>,
+}
+
+impl EndlessMaze {
+    /// An endless maze with nothing built yet.
+    pub fn new(seed: u64, algorithm: MazeAlgorithmKind) -> Self {
+        EndlessMaze { seed, algorithm, loaded: HashMap::new(), reached: HashSet::new() }
+    }
+
+    /// Builds the maze of the chunk at `coord`.
+    pub fn chunk(&self, coord: ChunkCoord) -> Maze {
+        Maze::generate_chunk(self.seed, coord, &self.algorithm)
+    }
+}
+
+/// Root of one built chunk; its walls and floor are children, so despawning it
+/// takes everything with it.
+#[derive(Component, Debug)]
+pub struct MazeChunk(pub ChunkCoord);
+
+/// Meshes and materials shared by every chunk.
+#[derive(Resource)]
+pub struct ChunkAssets {
+    wall_material: Handle<StandardMaterial>,
+    floor_material: Handle<StandardMaterial>,
+    floor_mesh: Handle<Mesh>,
+}
+
+/// Sets up the shared chunk assets and lighting, standing in for `spawn_maze`.
+pub fn setup_endless(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let chunk_width = CHUNK_SIZE as f32 * CELL_SIZE;
+    commands.insert_resource(ChunkAssets {
+        wall_material: materials.add(StandardMaterial {
+            base_color: WALL_COLOR,
+            emissive: WALL_EMISSIVE,
+            ..default()
+        }),
+        floor_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.05, 0.05, 0.05),
+            ..default()
+        }),
+        floor_mesh: meshes.add(Cuboid::new(chunk_width, 0.1, chunk_width)),
+    });
+    commands.insert_resource(AmbientLight {
+        color: Color::WHITE,
+        brightness: AMBIENT_BRIGHTNESS,
+        ..default()
+    });
+}
+
+/// Builds missing chunks within LOAD_RADIUS of the player and despawns those beyond
+/// UNLOAD_RADIUS.
+pub fn stream_chunks(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut endless: ResMut<EndlessMaze>,
+    assets: Res<ChunkAssets>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else { return; };
+    let (px, py) = chunk_at(player_transform.translation);
+
+    let mut unloaded = Vec::new();
+    for (&coord, &entity) in &endless.loaded {
+        if (coord.0 - px).abs().max((coord.1 - py).abs()) > UNLOAD_RADIUS {
+            commands.entity(entity).despawn_recursive();
+            unloaded.push(coord);
+        }
+    }
+    for coord in unloaded {
+        endless.loaded.remove(&coord);
+    }
+
+    for cy in py - LOAD_RADIUS..=py + LOAD_RADIUS {
+        for cx in px - LOAD_RADIUS..=px + LOAD_RADIUS {
+            if !endless.loaded.contains_key(&(cx, cy)) {
+                let entity = spawn_chunk(&mut commands, &mut meshes, &assets, &endless.chunk((cx, cy)), (cx, cy));
+                endless.loaded.insert((cx, cy), entity);
+            }
+        }
+    }
+}
+
+/// Spawns the floor and walls of one chunk under a `MazeChunk` root, the walls merged
+/// into one mesh and collider. Each chunk draws its own east and south borders, leaving
+/// gaps for the openings into the next chunk; its north and west borders belong to the
+/// chunks on the other side.
+fn spawn_chunk(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    assets: &ChunkAssets,
+    maze: &Maze,
+    coord: ChunkCoord,
+) -> Entity {
+    let half = CHUNK_SIZE as f32 * CELL_SIZE / 2.0;
+    let walls: Vec<(usize, usize, u16)> = (0..maze.height())
+        .flat_map(|y| (0..maze.width()).map(move |x| (x, y)))
+        .flat_map(|(x, y)| [(x, y, EAST), (x, y, SOUTH)])
+        .filter(|&(x, y, direction)| !maze.cell(x, y).is_open(direction))
+        .collect();
+    let walls = wall_batch(maze, &walls);
+    commands
+        .spawn((MazeChunk(coord), Transform::from_translation(chunk_origin(coord)), Visibility::default()))
+        .with_children(|chunk| {
+            chunk.spawn((
+                Mesh3d(assets.floor_mesh.clone()),
+                MeshMaterial3d(assets.floor_material.clone()),
+                Transform::from_xyz(half, -0.05, half),
+                Collider::cuboid(half, 0.05, half),
+                RigidBody::Fixed,
+            ));
+            if let Some((mesh, collider)) = walls {
+                chunk.spawn((
+                    MazeWall,
+                    Mesh3d(meshes.add(mesh)),
+                    MeshMaterial3d(assets.wall_material.clone()),
+                    Transform::IDENTITY,
+                    collider,
+                    RigidBody::Fixed,
+                ));
+            }
+        })
+        .id()
+}
+
+/// Puts time back on the clock the first time the player enters each chunk.
+pub fn reward_new_chunks(
+    mut endless: ResMut<EndlessMaze>,
+    mut timer: ResMut<GameTimer>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else { return; };
+    let coord = chunk_at(player_transform.translation);
+    if endless.reached.insert(coord) && endless.reached.len() > 1 {
+        timer.remaining = (timer.remaining + CHUNK_TIME_BONUS).min(timer.total);
+        info!("Reached chunk {:?} ({} so far)", coord, endless.reached.len());
+    }
+}
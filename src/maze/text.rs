@@ -0,0 +1,536 @@
+//! Plain-text maze format for hand-authored levels, fixtures, and bug reports.
+//!
+//! Each cell is drawn as a 3x2 block of characters: `+--` on top and `|  ` on the left.
+//! A wall is one character run (`--` or `|`), an opening is spaces. Corners are `+`.
+//! Cell interiors are two free characters that the parser ignores, except for
+//! floor links: `sv` / `lv` mark stairs / a lift down to the next floor, and the
+//! cell they arrive at is drawn `s^` / `l^`. Void cells outside a mask are drawn `##`.
+//! Floors are separated by a blank line.
+//! Lines starting with `#` are comments and count as blank lines.
+//!
+//! Everything else about the maze follows the floors as `@` lines, one feature per line,
+//! with cells in stacked maze coordinates (floor 1 starts at the row after floor 0):
+//! `@door x y east red`, `@key x y red`, `@portal x y x y`, `@one-way x y east` (open
+//! from (x, y) going east only), `@secret x y east`, `@tile x y sparking`, and
+//! `@room x y width height server-room`.
+//!
+//! ```text
+//! +--+--+
+//! |  sv |
+//! +  +--+
+//! |     |
+//! +--+--+
+//!
+//! +--+--+
+//! |  s^ |
+//! +--+  +
+//! |     |
+//! +--+--+
+//! @key 0 1 red
+//! @door 1 2 east red
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use crate::maze::doors::{Door, KeyColor, Keycard};
+use crate::maze::floors::FloorLinkKind;
+use crate::maze::generator::{Maze, NORTH, SOUTH, EAST, WEST};
+use crate::maze::rooms::{Room, RoomKind};
+use crate::maze::tiles::TileKind;
+
+/// Side names used by `@` lines.
+const DIRECTION_NAMES: [(u16, &str); 4] = [(NORTH, "north"), (SOUTH, "south"), (EAST, "east"), (WEST, "west")];
+
+fn direction_name(direction: u16) -> &'static str {
+    DIRECTION_NAMES
+        .iter()
+        .find_map(|&(dir, name)| (dir == direction).then_some(name))
+        .expect("the text format only has square sides")
+}
+
+/// Why a text maze could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MazeParseError {
+    /// The text had no maze lines at all.
+    Empty,
+    /// Each floor needs an odd number of lines (2 per row plus the closing border).
+    BadLineCount(usize),
+    /// A line is longer than the 3 characters per cell the first line implies.
+    BadLineWidth { line: usize, expected: usize, found: usize },
+    /// A wall slot held something other than a wall or a space.
+    UnexpectedChar { line: usize, column: usize, found: char },
+    /// A floor does not have the same size as the first one.
+    FloorSizeMismatch { floor: usize },
+    /// A link down was marked on the bottom floor.
+    LinkOffBottom { line: usize, column: usize },
+    /// An `@` line that is not a known feature, or names cells or sides the maze lacks.
+    BadFeature { line: usize, text: String },
+}
+
+impl fmt::Display for MazeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MazeParseError::Empty => write!(f, "maze text is empty"),
+            MazeParseError::BadLineCount(count) => {
+                write!(f, "expected an odd number of lines (at least 3), found {count}")
+            }
+            MazeParseError::BadLineWidth { line, expected, found } => {
+                write!(f, "line {line}: expected {expected} characters, found {found}")
+            }
+            MazeParseError::UnexpectedChar { line, column, found } => {
+                write!(f, "line {line}, column {column}: unexpected '{found}' in a wall slot")
+            }
+            MazeParseError::FloorSizeMismatch { floor } => {
+                write!(f, "floor {floor} is not the same size as floor 0")
+            }
+            MazeParseError::LinkOffBottom { line, column } => {
+                write!(f, "line {line}, column {column}: link down from the bottom floor")
+            }
+            MazeParseError::BadFeature { line, text } => {
+                write!(f, "line {line}: cannot place '{text}'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MazeParseError {}
+
+/// One floor read from text, before floors are stacked.
+struct ParsedFloor {
+    maze: Maze,
+    /// Cells marked with a link down, and where the marker was for error messages.
+    links_down: Vec<(usize, usize, FloorLinkKind, usize, usize)>,
+}
+
+impl Maze {
+    /// Renders the maze in the text format described at the top of this module.
+    pub fn to_ascii(&self) -> String {
+        let mut down: HashMap<(usize, usize), FloorLinkKind> = HashMap::new();
+        let mut up: HashMap<(usize, usize), FloorLinkKind> = HashMap::new();
+        for link in self.links() {
+            down.insert((link.x, link.y), link.kind);
+            up.insert(link.lower(self), link.kind);
+        }
+        let marker = |kind: FloorLinkKind| match kind {
+            FloorLinkKind::Stairs => 's',
+            FloorLinkKind::Lift => 'l',
+        };
+
+        let mut out = String::new();
+
+        for y in 0..self.height() {
+            if self.row_on_floor(y) == 0 {
+                if y > 0 {
+                    out.push('\n');
+                }
+                out.push('+');
+                for _ in 0..self.width() {
+                    out.push_str("--+");
+                }
+                out.push('\n');
+            }
+
+            // Cell row: west border, then each interior followed by its east wall.
+            out.push('|');
+            for x in 0..self.width() {
+                if !self.in_mask(x, y) {
+                    out.push_str("##");
+                } else if let Some(&kind) = down.get(&(x, y)) {
+                    out.push(marker(kind));
+                    out.push('v');
+                } else if let Some(&kind) = up.get(&(x, y)) {
+                    out.push(marker(kind));
+                    out.push('^');
+                } else {
+                    out.push_str("  ");
+                }
+                out.push(if self.cell(x, y).is_open(EAST) { ' ' } else { '|' });
+            }
+            out.push('\n');
+
+            // Wall row: each cell's south wall between corners.
+            out.push('+');
+            for x in 0..self.width() {
+                out.push_str(if self.cell(x, y).is_open(SOUTH) { "  " } else { "--" });
+                out.push('+');
+            }
+            out.push('\n');
+        }
+
+        let features = self.feature_lines();
+        if !features.is_empty() {
+            out.push('\n');
+            for line in features {
+                out.push_str(&line);
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    /// The `@` lines for everything the grid cannot show.
+    fn feature_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for room in self.rooms() {
+            lines.push(format!("@room {} {} {} {} {}", room.x, room.y, room.width, room.height, room.kind.name()));
+        }
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let cell = self.cell(x, y);
+                for &(dir, name) in &DIRECTION_NAMES {
+                    if cell.is_open(dir) && self.is_one_way(x, y, dir) {
+                        lines.push(format!("@one-way {x} {y} {name}"));
+                    }
+                }
+                for dir in [EAST, SOUTH] {
+                    if self.is_secret(x, y, dir) {
+                        lines.push(format!("@secret {x} {y} {}", direction_name(dir)));
+                    }
+                }
+                if cell.tile != TileKind::Plain {
+                    lines.push(format!("@tile {x} {y} {}", cell.tile.name()));
+                }
+            }
+        }
+        for portal in self.portals() {
+            lines.push(format!("@portal {} {} {} {}", portal.a.0, portal.a.1, portal.b.0, portal.b.1));
+        }
+        for door in self.doors() {
+            lines.push(format!("@door {} {} {} {}", door.x, door.y, direction_name(door.direction), door.color.name()));
+        }
+        for key in self.keycards() {
+            lines.push(format!("@key {} {} {}", key.x, key.y, key.color.name()));
+        }
+        lines
+    }
+
+    /// Applies one `@` line, or returns None if it cannot be read or does not fit.
+    fn apply_feature(&mut self, text: &str) -> Option<()> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let (&keyword, args) = words.split_first()?;
+        let number = |index: usize| args.get(index)?.parse::<usize>().ok();
+        let cell = |index: usize| {
+            let (x, y) = (number(index)?, number(index + 1)?);
+            (x < self.width() && y < self.height() && self.in_mask(x, y)).then_some((x, y))
+        };
+        let side = |index: usize| {
+            let name = *args.get(index)?;
+            DIRECTION_NAMES.iter().find_map(|&(dir, n)| (n == name).then_some(dir))
+        };
+        let word = |index: usize| args.get(index).copied();
+
+        match (keyword, args.len()) {
+            ("@room", 5) => {
+                let (x, y) = cell(0)?;
+                let (width, height) = (number(2)?, number(3)?);
+                let room = Room { x, y, width, height, kind: RoomKind::from_name(word(4)?)? };
+                let fits = width > 0 && height > 0 && x + width <= self.width() && y + height <= self.height();
+                (fits && self.floor_of(y) == self.floor_of(y + height - 1)).then_some(())?;
+                self.rooms_mut().push(room);
+            }
+            ("@one-way", 3) => {
+                let ((x, y), dir) = (cell(0)?, side(2)?);
+                self.step(x, y, dir)?;
+                self.carve_one_way(x, y, dir);
+            }
+            ("@secret", 3) => {
+                let ((x, y), dir) = (cell(0)?, side(2)?);
+                self.step(x, y, dir)?;
+                self.carve(x, y, dir);
+                self.hide_passage(x, y, dir);
+            }
+            ("@tile", 3) => {
+                let (x, y) = cell(0)?;
+                self.set_tile(x, y, TileKind::from_name(word(2)?)?);
+            }
+            ("@portal", 4) => {
+                let (a, b) = (cell(0)?, cell(2)?);
+                (a != b).then_some(())?;
+                self.link_portals(a, b);
+            }
+            ("@door", 4) => {
+                // A door can only lock a passage that is there to walk through.
+                let ((x, y), direction) = (cell(0)?, side(2)?);
+                self.cell(x, y).is_open(direction).then_some(())?;
+                let color = KeyColor::from_name(word(3)?)?;
+                self.doors_mut().push(Door { x, y, direction, color });
+            }
+            ("@key", 3) => {
+                let (x, y) = cell(0)?;
+                let color = KeyColor::from_name(word(2)?)?;
+                self.keycards_mut().push(Keycard { x, y, color });
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// Parses a maze from the text format described at the top of this module.
+    /// Trailing blank lines are ignored and short lines are padded with spaces,
+    /// so editors that strip trailing whitespace do not break a level.
+    /// The outer border is always treated as solid.
+    pub fn from_ascii(text: &str) -> Result<Maze, MazeParseError> {
+        // Feature lines are set aside until the floors are built, and read as blank here.
+        let mut features = Vec::new();
+        let lines: Vec<Vec<char>> = text
+            .trim_end()
+            .lines()
+            .enumerate()
+            .map(|(index, line)| {
+                if line.starts_with('@') {
+                    features.push((index + 1, line.trim_end()));
+                }
+                match line.starts_with('#') || line.starts_with('@') {
+                    true => Vec::new(),
+                    false => line.trim_end().chars().collect(),
+                }
+            })
+            .collect();
+
+        if lines.iter().all(|line| line.is_empty()) {
+            return Err(MazeParseError::Empty);
+        }
+
+        // Split into floors on blank lines, remembering where each one starts.
+        let mut blocks: Vec<(usize, &[Vec<char>])> = Vec::new();
+        let mut start = 0;
+        for (i, line) in lines.iter().enumerate() {
+            if line.is_empty() {
+                if i > start {
+                    blocks.push((start, &lines[start..i]));
+                }
+                start = i + 1;
+            }
+        }
+        if start < lines.len() {
+            blocks.push((start, &lines[start..]));
+        }
+
+        let floors = blocks
+            .iter()
+            .map(|&(first_line, block)| parse_floor(block, first_line))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let width = floors[0].maze.width();
+        let rows = floors[0].maze.height();
+        if let Some(floor) = floors
+            .iter()
+            .position(|f| f.maze.width() != width || f.maze.height() != rows)
+        {
+            return Err(MazeParseError::FloorSizeMismatch { floor });
+        }
+
+        let mut maze = Maze::new_floors(width, rows, floors.len());
+        for (index, floor) in floors.iter().enumerate() {
+            for row in 0..rows {
+                for x in 0..width {
+                    maze.set_in_mask(x, index * rows + row, floor.maze.in_mask(x, row));
+                    maze.cell_mut(x, index * rows + row).passages = floor.maze.cell(x, row).passages;
+                }
+            }
+        }
+        // Links go in once every floor is copied, so the floor below keeps its way up.
+        for (index, floor) in floors.iter().enumerate() {
+            for &(x, row, kind, line, column) in &floor.links_down {
+                if index + 1 == floors.len() {
+                    return Err(MazeParseError::LinkOffBottom { line, column });
+                }
+                maze.link_floors(x, index * rows + row, kind);
+            }
+        }
+
+        for (line, text) in features {
+            if maze.apply_feature(text).is_none() {
+                return Err(MazeParseError::BadFeature { line, text: text.to_string() });
+            }
+        }
+        Ok(maze)
+    }
+}
+
+/// Parses a single floor block. `first_line` is the block's 0-based line offset in the file.
+fn parse_floor(lines: &[Vec<char>], first_line: usize) -> Result<ParsedFloor, MazeParseError> {
+    if lines.len() < 3 || lines.len().is_multiple_of(2) {
+        return Err(MazeParseError::BadLineCount(lines.len()));
+    }
+
+    let line_width = lines[0].len();
+    if line_width < 4 || !(line_width - 1).is_multiple_of(3) {
+        return Err(MazeParseError::BadLineWidth {
+            line: first_line + 1,
+            expected: (line_width.saturating_sub(1) / 3).max(1) * 3 + 1,
+            found: line_width,
+        });
+    }
+    for (i, line) in lines.iter().enumerate() {
+        if line.len() > line_width {
+            return Err(MazeParseError::BadLineWidth {
+                line: first_line + i + 1,
+                expected: line_width,
+                found: line.len(),
+            });
+        }
+    }
+
+    let width = (line_width - 1) / 3;
+    let height = (lines.len() - 1) / 2;
+    let at = |line: usize, column: usize| lines[line].get(column).copied().unwrap_or(' ');
+    let mut maze = Maze::new(width, height);
+    let mut links_down = Vec::new();
+
+    // Void cells first, so the walls around them are never opened.
+    for y in 0..height {
+        for x in 0..width {
+            if at(2 * y + 1, 3 * x + 1) == '#' && at(2 * y + 1, 3 * x + 2) == '#' {
+                maze.set_in_mask(x, y, false);
+            }
+        }
+    }
+
+    for y in 0..height {
+        let cell_line = 2 * y + 1;
+        let wall_line = 2 * y + 2;
+
+        for x in 0..width {
+            // Interior: only link-down markers mean anything.
+            let column = 3 * x + 1;
+            if at(cell_line, column + 1) == 'v' {
+                let kind = match at(cell_line, column) {
+                    'l' => FloorLinkKind::Lift,
+                    _ => FloorLinkKind::Stairs,
+                };
+                links_down.push((x, y, kind, first_line + cell_line + 1, column + 2));
+            }
+
+            // East wall, skipping the outer border.
+            if x + 1 < width {
+                let column = 3 * x + 3;
+                match at(cell_line, column) {
+                    ' ' if maze.step(x, y, EAST).is_some() => maze.carve(x, y, EAST),
+                    ' ' => {}
+                    '|' => {}
+                    found => {
+                        return Err(MazeParseError::UnexpectedChar {
+                            line: first_line + cell_line + 1,
+                            column: column + 1,
+                            found,
+                        })
+                    }
+                }
+            }
+
+            // South wall, skipping the outer border.
+            if y + 1 < height {
+                let column = 3 * x + 1;
+                match (at(wall_line, column), at(wall_line, column + 1)) {
+                    (' ', ' ') if maze.step(x, y, SOUTH).is_some() => maze.carve(x, y, SOUTH),
+                    (' ', ' ') => {}
+                    ('-', '-') => {}
+                    (first, second) => {
+                        let (column, found) = if first == '-' || first == ' ' {
+                            (column + 1, second)
+                        } else {
+                            (column, first)
+                        };
+                        return Err(MazeParseError::UnexpectedChar {
+                            line: first_line + wall_line + 1,
+                            column: column + 1,
+                            found,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(ParsedFloor { maze, links_down })
+}
+
+impl fmt::Display for Maze {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_ascii())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::algorithms::MazeAlgorithmKind;
+    use crate::maze::mask::Mask;
+    use crate::maze::rooms::RoomsAndCorridors;
+    use crate::maze::topology::Topology;
+    use crate::rng::Rng;
+
+    /// A two-floor office with every feature the format has to carry.
+    fn furnished(seed: u64) -> Maze {
+        let algorithm = RoomsAndCorridors { corridors: MazeAlgorithmKind::Prim, rooms: 3 };
+        let mut maze = Maze::generate_floors(12, 10, 2, Topology::Square, seed, &algorithm);
+        let mut rng = Rng::new(seed);
+        let goal = (maze.width() - 1, maze.height() - 1);
+        maze.braid(0.3, &mut rng);
+        maze.place_portals(2, &mut rng);
+        maze.lock_doors(2, (0, 0), goal, &mut rng);
+        maze.add_one_way_passages(4, (0, 0), goal, &mut rng);
+        maze.add_secret_walls(3, &mut rng);
+        maze.sprinkle_hazards(0.2, &[(0, 0), goal], &mut rng);
+        maze
+    }
+
+    #[test]
+    fn round_trips_every_feature() {
+        for seed in [1, 7, 2024] {
+            let maze = furnished(seed);
+            assert!(!maze.doors().is_empty() && !maze.portals().is_empty() && !maze.rooms().is_empty());
+            let text = maze.to_ascii();
+            assert_eq!(Maze::from_ascii(&text), Ok(maze), "seed {seed}:\n{text}");
+        }
+    }
+
+    #[test]
+    fn round_trips_plain_mazes() {
+        for algorithm in MazeAlgorithmKind::ALL {
+            let plain = Maze::generate_with(9, 6, 3, &algorithm);
+            assert_eq!(Maze::from_ascii(&plain.to_ascii()), Ok(plain));
+        }
+    }
+
+    #[test]
+    fn round_trips_masked_mazes() {
+        for algorithm in MazeAlgorithmKind::ALL {
+            let masked = Maze::generate_masked(&Mask::circle(9), 3, &algorithm);
+            assert_eq!(Maze::from_ascii(&masked.to_ascii()), Ok(masked));
+        }
+    }
+
+    #[test]
+    fn rejects_features_that_do_not_fit() {
+        let grid = Maze::generate_with(3, 3, 1, &MazeAlgorithmKind::Prim).to_ascii();
+        let features = [
+            "@room 2 2 2 2 server-room",
+            "@door 2 0 east red",
+            "@key 9 9 red",
+            "@tile 0 0 lava",
+            "@portal 1 1 1 1",
+            "@teleport 0 0",
+        ];
+        for feature in features {
+            let text = format!("{grid}\n{feature}\n");
+            assert!(
+                matches!(Maze::from_ascii(&text), Err(MazeParseError::BadFeature { line: 9, .. })),
+                "{feature} was accepted",
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_doors_across_walls() {
+        let mut maze = Maze::new(3, 1);
+        maze.carve(0, 0, EAST);
+        let grid = maze.to_ascii();
+        assert!(Maze::from_ascii(&format!("{grid}\n@door 0 0 east red\n")).is_ok());
+        assert!(matches!(
+            Maze::from_ascii(&format!("{grid}\n@door 1 0 east red\n")),
+            Err(MazeParseError::BadFeature { line: 5, .. }),
+        ));
+    }
+}
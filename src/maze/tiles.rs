@@ -0,0 +1,92 @@
+//! Floor tiles — hazards laid over the maze that change how a cell plays.
+//! Tiles never close a passage, so sprinkling them cannot make a maze unsolvable.
+
+use crate::maze::generator::Maze;
+use crate::maze::placement::PlacementRule;
+use crate::rng::Rng;
+
+/// What the floor of a cell is like.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TileKind {
+    #[default]
+    Plain,
+    /// Live wiring through the floor; standing on it hurts.
+    Sparking,
+    /// Water from a burst pipe; wading through it is slow.
+    Flooded,
+    /// Blown fuses; no light in here.
+    Dark,
+    /// Interference that scrambles the HUD.
+    Static,
+}
+
+impl TileKind {
+    /// Every hazardous kind, in the order they are drawn when sprinkling.
+    pub const HAZARDS: [TileKind; 4] = [TileKind::Sparking, TileKind::Flooded, TileKind::Dark, TileKind::Static];
+
+    /// The name used in text mazes.
+    pub fn name(self) -> &'static str {
+        match self {
+            TileKind::Plain => "plain",
+            TileKind::Sparking => "sparking",
+            TileKind::Flooded => "flooded",
+            TileKind::Dark => "dark",
+            TileKind::Static => "static",
+        }
+    }
+
+    /// Looks a tile up by its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        std::iter::once(TileKind::Plain).chain(Self::HAZARDS).find(|tile| tile.name() == name)
+    }
+}
+
+impl Maze {
+    /// The floor tile at (x, y).
+    pub fn tile(&self, x: usize, y: usize) -> TileKind {
+        self.cell(x, y).tile
+    }
+
+    /// Changes the floor tile at (x, y).
+    pub fn set_tile(&mut self, x: usize, y: usize, tile: TileKind) {
+        self.cell_mut(x, y).tile = tile;
+    }
+
+    /// Turns about `density` (0.0 to 1.0) of the cells into random hazards, leaving the
+    /// cells in `keep_clear` plain. Returns how many cells became hazards.
+    pub fn sprinkle_hazards(&mut self, density: f32, keep_clear: &[(usize, usize)], rng: &mut Rng) -> usize {
+        let mut cells = self.cells_where(&[PlacementRule::Avoid(keep_clear.to_vec())]);
+        rng.shuffle(&mut cells);
+        let count = (cells.len() as f32 * density.clamp(0.0, 1.0)).round() as usize;
+        for &(x, y) in &cells[..count] {
+            let kind = TileKind::HAZARDS[rng.next_usize(TileKind::HAZARDS.len())];
+            self.set_tile(x, y, kind);
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::algorithms::MazeAlgorithmKind;
+
+    #[test]
+    fn hazards_cover_their_share_and_spare_the_kept_cells() {
+        let keep = [(0, 0), (9, 9)];
+        let sprinkle = |density: f32| {
+            let mut maze = Maze::generate_with(10, 10, 4, &MazeAlgorithmKind::default());
+            let count = maze.sprinkle_hazards(density, &keep, &mut Rng::new(6));
+            (maze, count)
+        };
+        for (density, expected) in [(0.0, 0), (0.25, 25), (1.0, 98)] {
+            let (maze, count) = sprinkle(density);
+            let hazards = (0..10).flat_map(|y| (0..10).map(move |x| (x, y)))
+                .filter(|&(x, y)| maze.tile(x, y) != TileKind::Plain)
+                .count();
+            assert_eq!((count, hazards), (expected, expected), "density {density}");
+            assert!(keep.iter().all(|&(x, y)| maze.tile(x, y) == TileKind::Plain), "density {density}");
+            assert_eq!(maze, sprinkle(density).0, "density {density}");
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! Grid topologies — which cells count as neighbors and in which direction.
+//! Square grids have four sides, hex grids six, and polar (theta) mazes wrap
+//! each row into a ring around a central hub.
+
+use crate::maze::generator::{NORTH, SOUTH, EAST, WEST, NORTH_EAST, NORTH_WEST, SOUTH_EAST, SOUTH_WEST};
+
+/// The shape of the cells and how they tile.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Topology {
+    /// Four-sided cells in rows and columns.
+    #[default]
+    Square,
+    /// Pointy-topped hexagons in offset rows; odd rows sit half a cell to the east.
+    Hex,
+    /// Concentric rings: x is the sector and wraps around, NORTH points in toward
+    /// the hub and SOUTH out toward the rim. Every ring has the same number of sectors.
+    Polar,
+}
+
+impl Topology {
+    /// All topologies in declaration order.
+    pub const ALL: [Topology; 3] = [Topology::Square, Topology::Hex, Topology::Polar];
+
+    /// The name used on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Topology::Square => "square",
+            Topology::Hex => "hex",
+            Topology::Polar => "polar",
+        }
+    }
+
+    /// Looks a topology up by its command line name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|topology| topology.name() == name)
+    }
+
+    /// Every planar direction a cell can open, in the order generators consider them.
+    pub fn directions(self) -> &'static [u16] {
+        match self {
+            Topology::Square | Topology::Polar => &[NORTH, SOUTH, EAST, WEST],
+            Topology::Hex => &[NORTH_EAST, NORTH_WEST, SOUTH_EAST, SOUTH_WEST, EAST, WEST],
+        }
+    }
+
+    /// The directions that name each shared wall exactly once: every inner wall is one
+    /// of these sides of some cell. Used to list walls and edges without duplicates.
+    pub fn forward_directions(self) -> &'static [u16] {
+        match self {
+            Topology::Square | Topology::Polar => &[EAST, SOUTH],
+            Topology::Hex => &[EAST, SOUTH_EAST, SOUTH_WEST],
+        }
+    }
+
+    /// The cell one step from (x, row) on a single `width` x `rows` floor, if there is one.
+    pub(crate) fn step(self, x: usize, row: usize, width: usize, rows: usize, direction: u16) -> Option<(usize, usize)> {
+        let north = row > 0;
+        let south = row + 1 < rows;
+        let east = x + 1 < width;
+        let west = x > 0;
+        match self {
+            Topology::Square => match direction {
+                NORTH if north => Some((x, row - 1)),
+                SOUTH if south => Some((x, row + 1)),
+                EAST if east => Some((x + 1, row)),
+                WEST if west => Some((x - 1, row)),
+                _ => None,
+            },
+            Topology::Polar => match direction {
+                NORTH if north => Some((x, row - 1)),
+                SOUTH if south => Some((x, row + 1)),
+                EAST => Some(((x + 1) % width, row)),
+                WEST => Some(((x + width - 1) % width, row)),
+                _ => None,
+            },
+            Topology::Hex => {
+                // Diagonal neighbors share the column on one side and shift on the other,
+                // depending on whether this row is pushed east.
+                let shifted = row % 2 == 1;
+                let left = if shifted { Some(x) } else { x.checked_sub(1) };
+                let right = if shifted { east.then_some(x + 1) } else { Some(x) };
+                match direction {
+                    EAST if east => Some((x + 1, row)),
+                    WEST if west => Some((x - 1, row)),
+                    NORTH_WEST if north => left.map(|nx| (nx, row - 1)),
+                    NORTH_EAST if north => right.map(|nx| (nx, row - 1)),
+                    SOUTH_WEST if south => left.map(|nx| (nx, row + 1)),
+                    SOUTH_EAST if south => right.map(|nx| (nx, row + 1)),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
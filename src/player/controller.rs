@@ -4,6 +4,14 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 use bevy::input::mouse::MouseMotion;
+use crate::maze::generator::Maze;
+use crate::maze::hazards::{CurrentTile, FLOOD_SLOWDOWN};
+use crate::maze::tiles::TileKind;
+use crate::maze::placement::Placements;
+use crate::maze::spatial::cell_center;
+
+/// Radius of the player's capsule collider.
+pub const PLAYER_RADIUS: f32 = 0.3;
 
 /// Marker component for the player entity.
 #[derive(Component)]
@@ -26,15 +34,16 @@ impl Default for MouseSensitivity {
     }
 }
 
-/// Spawns the player camera at maze start position.
-pub fn spawn_player(mut commands: Commands) {
+/// Spawns the player camera at the placed start cell.
+pub fn spawn_player(mut commands: Commands, maze: Res<Maze>, placements: Res<Placements>) {
+    let (x, y) = placements.player;
     commands.spawn((
         Player,
         PlayerLook { yaw: 0.0, pitch: 0.0 },
         Camera3d::default(),
-        Transform::from_xyz(1.0, 0.5, 1.0),
+        Transform::from_translation(cell_center(&maze, x, y) + Vec3::Y * 0.5),
         RigidBody::Dynamic,
-        Collider::capsule_y(0.4, 0.3),
+        Collider::capsule_y(0.4, PLAYER_RADIUS),
         LockedAxes::ROTATION_LOCKED,
         Velocity::zero(),
     ));
@@ -62,14 +71,15 @@ pub fn player_look(
     transform.rotation = Quat::from_euler(EulerRot::YXZ, look.yaw, look.pitch, 0.0);
 }
 
-/// Handles WASD movement relative to camera facing direction.
+/// Handles WASD movement relative to camera facing direction. Flooded cells slow it down.
 pub fn player_move(
     keys: Res<ButtonInput<KeyCode>>,
     time: Res<Time>,
+    tile: Res<CurrentTile>,
     mut query: Query<(&mut Transform, &PlayerLook), With<Player>>,
 ) {
     let (mut transform, look) = query.single_mut();
-    let speed = 4.0;
+    let speed = if tile.0 == TileKind::Flooded { 4.0 * FLOOD_SLOWDOWN } else { 4.0 };
     let forward = Vec3::new(look.yaw.sin(), 0.0, look.yaw.cos());
     let right   = Vec3::new(look.yaw.cos(), 0.0, -look.yaw.sin());
     let mut velocity = Vec3::ZERO;
@@ -0,0 +1,165 @@
+//! Minimal PNG encoder for 8-bit RGB images, with no dependencies.
+//! Pixel rows go into stored (uncompressed) deflate blocks: larger files, but
+//! nothing to get wrong, and plenty for map dumps and debug images.
+
+/// The eight bytes every PNG file starts with.
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Largest payload a single stored deflate block can hold.
+const MAX_STORED_BLOCK: usize = 65_535;
+
+/// Encodes row-major RGB pixels as a PNG file.
+pub fn encode_rgb(width: u32, height: u32, pixels: &[[u8; 3]]) -> Vec<u8> {
+    assert_eq!(pixels.len(), (width * height) as usize, "pixels must fill the image");
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 8 bits per channel, truecolor, default compression/filter, no interlace
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    // Each row is prefixed with filter type 0 (none).
+    let mut raw = Vec::with_capacity(pixels.len() * 3 + height as usize);
+    for row in pixels.chunks(width.max(1) as usize) {
+        raw.push(0);
+        raw.extend(row.iter().flatten());
+    }
+
+    let mut png = SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+/// Appends one length-prefixed, CRC-checked chunk.
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps `data` in a zlib stream made of stored deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+/// CRC-32 (IEEE) as used by PNG chunks.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// Adler-32 checksum closing a zlib stream.
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65_521;
+        b = (b + a) % 65_521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Splits a PNG file into its chunks, checking each one's CRC.
+    fn chunks(png: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+        assert_eq!(png[..8], SIGNATURE);
+        let mut chunks = Vec::new();
+        let mut rest = &png[8..];
+        while !rest.is_empty() {
+            let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let (body, crc) = rest[4..].split_at(4 + len);
+            assert_eq!(crc32(body), u32::from_be_bytes(crc[..4].try_into().unwrap()));
+            chunks.push((body[..4].try_into().unwrap(), body[4..].to_vec()));
+            rest = &crc[4..];
+        }
+        chunks
+    }
+
+    /// Reads the stored blocks of a zlib stream back out, checking its Adler-32.
+    fn unstore(zlib: &[u8]) -> Vec<u8> {
+        assert_eq!(zlib[..2], [0x78, 0x01]);
+        let mut data = Vec::new();
+        let mut rest = &zlib[2..];
+        loop {
+            let last = rest[0] == 1;
+            let len = u16::from_le_bytes([rest[1], rest[2]]);
+            assert_eq!(!len, u16::from_le_bytes([rest[3], rest[4]]));
+            data.extend_from_slice(&rest[5..5 + len as usize]);
+            rest = &rest[5 + len as usize..];
+            if last {
+                break;
+            }
+        }
+        assert_eq!(rest, adler32(&data).to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn checksums_match_known_vectors() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(adler32(b""), 1);
+    }
+
+    #[test]
+    fn encodes_header_rows_and_pixels() {
+        let (width, height) = (3, 2);
+        let pixels: Vec<[u8; 3]> = (0..6).map(|i| [i, i * 10, 255 - i]).collect();
+        let png = encode_rgb(width, height, &pixels);
+        let chunks = chunks(&png);
+        let kinds: Vec<&[u8; 4]> = chunks.iter().map(|(kind, _)| kind).collect();
+        assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+
+        let header = &chunks[0].1;
+        assert_eq!(header.len(), 13);
+        assert_eq!(header[..8], [0, 0, 0, 3, 0, 0, 0, 2]);
+        assert_eq!(header[8..], [8, 2, 0, 0, 0]);
+
+        let raw = unstore(&chunks[1].1);
+        assert_eq!(raw.len(), (1 + 3 * width as usize) * height as usize);
+        for (row, bytes) in raw.chunks(1 + 3 * width as usize).enumerate() {
+            assert_eq!(bytes[0], 0, "row {row} filter");
+            let expected: Vec<u8> = pixels[row * 3..row * 3 + 3].iter().flatten().copied().collect();
+            assert_eq!(bytes[1..], expected);
+        }
+        assert!(chunks[2].1.is_empty());
+    }
+
+    #[test]
+    fn large_images_span_several_stored_blocks() {
+        let pixels = vec![[7, 8, 9]; 200 * 200];
+        let png = encode_rgb(200, 200, &pixels);
+        let raw = unstore(&chunks(&png)[1].1);
+        assert!(raw.len() > MAX_STORED_BLOCK);
+        assert_eq!(raw.len(), 200 * (1 + 600));
+        assert!(raw.chunks(601).all(|row| row[0] == 0 && row[1..4] == [7, 8, 9]));
+    }
+}
@@ -0,0 +1,108 @@
+//! Maze recipe — every option that changes which maze a seed generates, read from
+//! the command line once. Daily runs ignore those flags and use a fixed recipe, so
+//! everyone racing a day's maze races the same one.
+
+use bevy::prelude::*;
+use crate::cli::{arg_value, has_flag};
+use crate::maze::algorithms::MazeAlgorithmKind;
+use crate::maze::stats::Difficulty;
+use crate::maze::topology::Topology;
+
+/// Command line flags that feed the recipe.
+pub const RECIPE_FLAGS: [&str; 14] = [
+    "--algorithm", "--braid", "--portals", "--topology", "--rooms", "--floors", "--mask",
+    "--difficulty", "--doors", "--one-way", "--secret-walls", "--hazards", "--maze-file", "--endless",
+];
+
+/// How the run's maze is built from its seed.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct MazeRecipe {
+    pub algorithm: MazeAlgorithmKind,
+    /// Share of dead ends to open up.
+    pub braid: f32,
+    pub portals: usize,
+    pub topology: Topology,
+    pub rooms: usize,
+    pub floors: usize,
+    /// A mask file, or `circle`.
+    pub mask: Option<String>,
+    pub difficulty: Option<Difficulty>,
+    pub doors: usize,
+    pub one_way: usize,
+    pub secret_walls: usize,
+    /// Share of cells turned into hazard tiles.
+    pub hazards: f32,
+    /// A hand-authored text maze to load instead of generating one.
+    pub maze_file: Option<String>,
+    /// Stream the maze in chunks forever instead of building one.
+    pub endless: bool,
+}
+
+impl Default for MazeRecipe {
+    fn default() -> Self {
+        MazeRecipe {
+            algorithm: MazeAlgorithmKind::default(),
+            braid: 0.0,
+            portals: 0,
+            topology: Topology::default(),
+            rooms: 0,
+            floors: 1,
+            mask: None,
+            difficulty: None,
+            doors: 0,
+            one_way: 0,
+            secret_walls: 0,
+            hazards: 0.0,
+            maze_file: None,
+            endless: false,
+        }
+    }
+}
+
+impl MazeRecipe {
+    /// Reads the recipe from the command line, warning about values it cannot use.
+    pub fn from_args() -> Self {
+        let count = |flag: &str| arg_value(flag).and_then(|value| value.parse::<usize>().ok()).unwrap_or(0);
+        let share = |flag: &str| arg_value(flag).and_then(|value| value.parse::<f32>().ok()).unwrap_or(0.0);
+        MazeRecipe {
+            algorithm: match arg_value("--algorithm") {
+                Some(name) => MazeAlgorithmKind::from_name(&name).unwrap_or_else(|| {
+                    warn!("Unknown maze algorithm '{}', using backtracker", name);
+                    MazeAlgorithmKind::default()
+                }),
+                None => MazeAlgorithmKind::default(),
+            },
+            braid: share("--braid"),
+            portals: count("--portals"),
+            topology: match arg_value("--topology") {
+                Some(name) => Topology::from_name(&name).unwrap_or_else(|| {
+                    warn!("Unknown topology '{}', using square", name);
+                    Topology::default()
+                }),
+                None => Topology::default(),
+            },
+            rooms: count("--rooms"),
+            floors: count("--floors").max(1),
+            mask: arg_value("--mask"),
+            difficulty: arg_value("--difficulty").and_then(|name| {
+                let difficulty = Difficulty::from_name(&name);
+                if difficulty.is_none() {
+                    warn!("Unknown difficulty '{}', ignoring it", name);
+                }
+                difficulty
+            }),
+            doors: count("--doors"),
+            one_way: count("--one-way"),
+            secret_walls: count("--secret-walls"),
+            hazards: share("--hazards"),
+            maze_file: arg_value("--maze-file"),
+            endless: has_flag("--endless"),
+        }
+    }
+
+    /// The recipe every daily run uses: the plain default maze, the same one daily
+    /// runs have always had, so saved best times stay comparable.
+    pub fn daily() -> Self {
+        MazeRecipe::default()
+    }
+}
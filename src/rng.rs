@@ -0,0 +1,232 @@
+//! Seedable game RNG — one run seed fans out into named, independent sub-streams.
+//! The generator is PCG32 with unbiased range reduction, and its output is part of
+//! the save/replay contract: bump RNG_VERSION whenever a change alters the numbers.
+
+use bevy::prelude::*;
+
+/// Version of the random number sequence. Seeds only reproduce a layout within one version.
+pub const RNG_VERSION: u32 = 2;
+
+/// PCG32 (XSH-RR) pseudo-random number generator.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+    increment: u64,
+}
+
+const PCG_MULTIPLIER: u64 = 6364136223846793005;
+const PCG_INCREMENT: u64 = 1442695040888963407;
+
+impl Rng {
+    /// Creates a new RNG with a fixed seed for reproducibility.
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Rng { state: 0, increment: PCG_INCREMENT };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    /// Returns the next 32 random bits.
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(PCG_MULTIPLIER).wrapping_add(self.increment);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    /// Returns the next pseudo-random usize in range [0, max), without modulo bias.
+    pub fn next_usize(&mut self, max: usize) -> usize {
+        assert!(max > 0 && max <= u32::MAX as usize, "range must be 1..=u32::MAX");
+        // Lemire's multiply-shift, rejecting the few values that would skew the result.
+        let range = max as u32;
+        let threshold = range.wrapping_neg() % range;
+        loop {
+            let product = self.next_u32() as u64 * range as u64;
+            if product as u32 >= threshold {
+                return (product >> 32) as usize;
+            }
+        }
+    }
+
+    /// Returns a float in [0, 1).
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Returns true roughly half of the time.
+    pub fn next_bool(&mut self) -> bool {
+        self.next_u32() & 1 == 0
+    }
+
+    /// Shuffles a slice in place (Fisher-Yates).
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.next_usize(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+/// SplitMix64 finalizer: scrambles a 64-bit value so nearby inputs give unrelated outputs.
+pub fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Derives an independent seed from `seed` for the purpose named by `salt`.
+pub fn derive_seed(seed: u64, salt: u64) -> u64 {
+    splitmix64(seed ^ splitmix64(salt))
+}
+
+/// The independent random sequences a run draws from.
+/// Adding a stream never changes the numbers the existing ones produce.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RngStream {
+    Maze,
+    Enemies,
+    Loot,
+    Puzzle,
+}
+
+impl RngStream {
+    /// Every stream, in the order they are stored.
+    pub const ALL: [RngStream; 4] = [
+        RngStream::Maze,
+        RngStream::Enemies,
+        RngStream::Loot,
+        RngStream::Puzzle,
+    ];
+
+    /// Fixed salt that separates this stream from the others. Never renumber these.
+    fn salt(self) -> u64 {
+        match self {
+            RngStream::Maze => 1,
+            RngStream::Enemies => 2,
+            RngStream::Loot => 3,
+            RngStream::Puzzle => 4,
+        }
+    }
+}
+
+/// Run-wide RNG resource: one seed, one live generator per stream.
+#[derive(Resource, Clone, Debug)]
+pub struct GameRng {
+    seed: u64,
+    streams: [Rng; 4],
+}
+
+impl GameRng {
+    /// Creates every stream from the run seed.
+    pub fn new(seed: u64) -> Self {
+        GameRng {
+            seed,
+            streams: RngStream::ALL.map(|stream| Rng::new(derive_seed(seed, stream.salt()))),
+        }
+    }
+
+    /// The run seed all streams were derived from.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Starting seed of `stream`, for code that builds its own `Rng` (like maze generation).
+    pub fn stream_seed(&self, stream: RngStream) -> u64 {
+        derive_seed(self.seed, stream.salt())
+    }
+
+    /// The live generator for `stream`. Draws here never disturb the other streams.
+    pub fn stream(&mut self, stream: RngStream) -> &mut Rng {
+        let index = RngStream::ALL
+            .iter()
+            .position(|&s| s == stream)
+            .expect("every stream is in ALL");
+        &mut self.streams[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequence_is_pinned_to_the_version() {
+        // Changing these numbers breaks every shared seed: bump RNG_VERSION with them.
+        let mut rng = Rng::new(42);
+        let first: Vec<u32> = (0..4).map(|_| rng.next_u32()).collect();
+        assert_eq!(first, [3270867926, 1795671209, 1924641435, 1143034755]);
+        assert_eq!(RNG_VERSION, 2);
+    }
+
+    #[test]
+    fn next_usize_stays_in_range() {
+        let mut rng = Rng::new(7);
+        for max in [1, 2, 3, 7, 10, 1000, 1 << 31, u32::MAX as usize] {
+            for _ in 0..2000 {
+                assert!(rng.next_usize(max) < max, "out of range for {max}");
+            }
+        }
+        assert!((0..100).all(|_| rng.next_usize(1) == 0));
+    }
+
+    #[test]
+    fn next_usize_covers_small_ranges_evenly() {
+        let mut rng = Rng::new(11);
+        let mut counts = [0usize; 6];
+        for _ in 0..60_000 {
+            counts[rng.next_usize(6)] += 1;
+        }
+        assert!(counts.iter().all(|&count| count.abs_diff(10_000) < 500), "{counts:?}");
+    }
+
+    #[test]
+    fn next_usize_rejects_biased_draws() {
+        // With a range of 3 * 2^30, every third value would get two of the 2^32 raw draws
+        // mapped onto it instead of one, landing half the time rather than a third.
+        let range = 3 << 30;
+        let mut rng = Rng::new(3);
+        let draws = 30_000;
+        let every_third = (0..draws).filter(|_| rng.next_usize(range).is_multiple_of(3)).count();
+        let share = every_third as f64 / draws as f64;
+        assert!((share - 1.0 / 3.0).abs() < 0.02, "share {share}");
+    }
+
+    #[test]
+    #[should_panic(expected = "range must be")]
+    fn next_usize_refuses_an_empty_range() {
+        Rng::new(1).next_usize(0);
+    }
+
+    #[test]
+    fn streams_are_independent() {
+        let mut busy = GameRng::new(99);
+        let mut idle = GameRng::new(99);
+        for _ in 0..1000 {
+            busy.stream(RngStream::Maze).next_u32();
+            busy.stream(RngStream::Loot).next_usize(17);
+        }
+        for stream in [RngStream::Enemies, RngStream::Puzzle] {
+            let a: Vec<u32> = (0..50).map(|_| busy.stream(stream).next_u32()).collect();
+            let b: Vec<u32> = (0..50).map(|_| idle.stream(stream).next_u32()).collect();
+            assert_eq!(a, b, "{stream:?} moved when other streams were drawn from");
+        }
+    }
+
+    #[test]
+    fn streams_differ_and_match_their_seeds() {
+        let mut rng = GameRng::new(5);
+        let mut firsts = Vec::new();
+        for stream in RngStream::ALL {
+            let mut fresh = Rng::new(rng.stream_seed(stream));
+            let first: Vec<u32> = (0..8).map(|_| rng.stream(stream).next_u32()).collect();
+            assert_eq!(first, (0..8).map(|_| fresh.next_u32()).collect::<Vec<_>>());
+            firsts.push(first);
+        }
+        for (i, a) in firsts.iter().enumerate() {
+            assert!(firsts[i + 1..].iter().all(|b| a != b), "two streams share a sequence");
+        }
+        assert_ne!(GameRng::new(5).stream_seed(RngStream::Maze), GameRng::new(6).stream_seed(RngStream::Maze));
+    }
+}
@@ -0,0 +1,38 @@
+//! Run seed — the number the whole maze layout is derived from.
+//! Random per run unless `--seed <n>` is passed, and shown on screen so it can be shared.
+
+use bevy::prelude::*;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::cli::arg_value;
+use crate::rng::splitmix64;
+
+/// The seed the whole run (maze, enemies, loot, puzzle) is generated from.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunSeed(pub u64);
+
+impl RunSeed {
+    /// Uses `--seed <n>` from the command line if given, otherwise a time-derived seed.
+    pub fn from_args() -> Self {
+        match arg_value("--seed") {
+            Some(value) => match value.trim().parse::<u64>() {
+                Ok(seed) => RunSeed(seed),
+                Err(_) => {
+                    warn!("Invalid seed '{}', picking a random one", value);
+                    RunSeed::from_time()
+                }
+            },
+            None => RunSeed::from_time(),
+        }
+    }
+
+    /// Derives a seed from the current system time.
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or_default();
+        // Scramble the clock bits so consecutive launches look unrelated, and keep
+        // seeds short enough to read out and type back in.
+        RunSeed(splitmix64(nanos) % 1_000_000_000)
+    }
+}
@@ -4,16 +4,23 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 use crate::terminal::spawner::Terminal;
-use crate::maze::renderer::CELL_SIZE;
+use crate::maze::generator::Maze;
+use crate::maze::placement::Placements;
+use crate::maze::spatial::cell_center;
 
 /// Spawns a CRT monitor made of cuboids at the terminal position.
 pub fn spawn_monitor(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    maze: Res<Maze>,
+    placements: Res<Placements>,
 ) {
-    let base_x = CELL_SIZE * 19.5;
-    let base_z = CELL_SIZE * 19.5;
+    let (x, y) = placements.terminal;
+    let base = cell_center(&maze, x, y);
+    let base_x = base.x;
+    let base_y = base.y;
+    let base_z = base.z;
 
     let _base_y_offset = -0.5;
 
@@ -37,7 +44,7 @@ pub fn spawn_monitor(
     commands.spawn((
         Mesh3d(meshes.add(Cuboid::new(0.8, 0.15, 0.7))),
         MeshMaterial3d(casing_color.clone()),
-        Transform::from_xyz(base_x, 0.75, base_z),
+        Transform::from_xyz(base_x, base_y + 0.75, base_z),
         RigidBody::Fixed,
         Collider::cuboid(0.4, 0.075, 0.35),
     ));
@@ -46,7 +53,7 @@ pub fn spawn_monitor(
     commands.spawn((
         Mesh3d(meshes.add(Cuboid::new(0.7, 0.55, 0.45))),
         MeshMaterial3d(casing_color.clone()),
-        Transform::from_xyz(base_x, 1.45, base_z + 0.1),
+        Transform::from_xyz(base_x, base_y + 1.45, base_z + 0.1),
         RigidBody::Fixed,
         Collider::cuboid(0.35, 0.275, 0.225),
     ));
@@ -55,7 +62,7 @@ pub fn spawn_monitor(
     commands.spawn((
         Mesh3d(meshes.add(Cuboid::new(0.65, 0.5, 0.05))),
         MeshMaterial3d(bezel_color),
-        Transform::from_xyz(base_x, 1.45, base_z - 0.125),
+        Transform::from_xyz(base_x, base_y + 1.45, base_z - 0.125),
     ));
 
     // Screen (recessed slightly into bezel)
@@ -63,14 +70,14 @@ pub fn spawn_monitor(
         Terminal,
         Mesh3d(meshes.add(Cuboid::new(0.52, 0.38, 0.02))),
         MeshMaterial3d(screen_color),
-        Transform::from_xyz(base_x, 1.47, base_z - 0.14),
+        Transform::from_xyz(base_x, base_y + 1.47, base_z - 0.14),
     ));
 
     // Keyboard (flat box in front)
     commands.spawn((
         Mesh3d(meshes.add(Cuboid::new(0.6, 0.04, 0.22))),
         MeshMaterial3d(casing_color.clone()),
-        Transform::from_xyz(base_x, 0.84, base_z - 0.55),
+        Transform::from_xyz(base_x, base_y + 0.84, base_z - 0.55),
         RigidBody::Fixed,
         Collider::cuboid(0.3, 0.02, 0.11),
     ));
@@ -83,6 +90,6 @@ pub fn spawn_monitor(
             emissive: LinearRgba::new(0.0, 3.0, 0.0, 1.0),
             ..default()
         })),
-        Transform::from_xyz(base_x + 0.28, 1.22, base_z - 0.15),
+        Transform::from_xyz(base_x + 0.28, base_y + 1.22, base_z - 0.15),
     ));
 }
\ No newline at end of file
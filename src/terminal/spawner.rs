@@ -3,7 +3,9 @@
 
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
-use crate::maze::renderer::CELL_SIZE;
+use crate::maze::generator::Maze;
+use crate::maze::placement::Placements;
+use crate::maze::spatial::cell_center;
 
 /// Marker component for terminal entities.
 #[derive(Component)]
@@ -12,16 +14,20 @@ pub struct Terminal;
 /// How close the player must be to interact.
 pub const INTERACT_RANGE: f32 = 2.0;
 
-/// Spawns the terminal at a fixed position for now.
-/// Future: randomize position across valid maze cells.
+/// Spawns the terminal in its placed cell, the farthest one from the start on the deepest floor.
 pub fn spawn_terminal(
     mut commands: Commands,
     _meshes: ResMut<Assets<Mesh>>,
     _materials: ResMut<Assets<StandardMaterial>>,
+    maze: Res<Maze>,
+    placements: Res<Placements>,
 ) {
+    let (x, y) = placements.terminal;
+    let base = cell_center(&maze, x, y);
+
     commands.spawn((
         Terminal,
-        Transform::from_xyz(CELL_SIZE * 19.5, 0.5, CELL_SIZE * 19.5),
+        Transform::from_translation(base + Vec3::Y * 0.5),
         Collider::cuboid(0.3, 0.5, 0.2),
         RigidBody::Fixed,
     ));
@@ -3,12 +3,15 @@
 
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
+use crate::seed::RunSeed;
+use crate::daily::DailyChallenge;
 
 /// Renders the game over screen.
 pub fn render_game_over(
     mut contexts: EguiContexts,
     mut next_state: ResMut<NextState<crate::states::GameState>>,
     keys: Res<ButtonInput<KeyCode>>,
+    seed: Res<RunSeed>,
 ) {
     let ctx = contexts.ctx_mut();
 
@@ -22,7 +25,9 @@ pub fn render_game_over(
                 ui.label(egui::RichText::new("SYSTEM FAILURE").monospace().size(48.0));
                 ui.add_space(20.0);
                 ui.label(egui::RichText::new("THE Y2K BUG HAS CONSUMED THE SYSTEM").monospace().size(18.0));
-                ui.add_space(40.0);
+                ui.add_space(20.0);
+                ui.label(egui::RichText::new(format!("SEED {}", seed.0)).monospace().size(16.0));
+                ui.add_space(20.0);
                 ui.visuals_mut().override_text_color = Some(egui::Color32::from_rgb(0, 255, 70));
                 ui.label(egui::RichText::new("[PRESS ENTER TO RESTART]").monospace().size(16.0));
             });
@@ -33,11 +38,13 @@ pub fn render_game_over(
         });
 }
 
-/// Renders the win screen.
+/// Renders the win screen, with the date and times for a Daily Bug run.
 pub fn render_win(
     mut contexts: EguiContexts,
     mut next_state: ResMut<NextState<crate::states::GameState>>,
     keys: Res<ButtonInput<KeyCode>>,
+    seed: Res<RunSeed>,
+    daily: Option<Res<DailyChallenge>>,
 ) {
     let ctx = contexts.ctx_mut();
 
@@ -51,7 +58,16 @@ pub fn render_win(
                 ui.label(egui::RichText::new("Y2K BUG PATCHED").monospace().size(48.0));
                 ui.add_space(20.0);
                 ui.label(egui::RichText::new("SYSTEM RESTORED — HUMANITY SAVED").monospace().size(18.0));
-                ui.add_space(40.0);
+                ui.add_space(20.0);
+                ui.label(egui::RichText::new(format!("SEED {}", seed.0)).monospace().size(16.0));
+                if let Some(daily) = &daily {
+                    let time = |secs: Option<f32>| secs.map_or("--".to_string(), |secs| format!("{:.2}s", secs));
+                    ui.add_space(10.0);
+                    ui.label(egui::RichText::new(format!("DAILY BUG {}", daily.date)).monospace().size(16.0));
+                    ui.label(egui::RichText::new(format!("TIME {}   BEST {}", time(daily.last), time(daily.best)))
+                        .monospace().size(16.0));
+                }
+                ui.add_space(20.0);
                 ui.label(egui::RichText::new("[PRESS ENTER TO PLAY AGAIN]").monospace().size(16.0));
             });
 
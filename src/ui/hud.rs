@@ -3,12 +3,32 @@
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
 use crate::game_timer::GameTimer;
+use crate::seed::RunSeed;
+use crate::maze::keycards::Keyring;
+use crate::maze::hazards::CurrentTile;
+use crate::maze::jumps::JumpGlitch;
+use crate::maze::streaming::EndlessMaze;
+use crate::maze::tiles::TileKind;
+use crate::rng::splitmix64;
 
-/// Renders the countdown timer in a rounded box and a dot crosshair.
+/// Glyphs the readouts turn into while standing in static.
+const STATIC_GLYPHS: &[u8] = b"#%&@$*!?01";
+
+/// Glitch bars drawn across the screen at the start of a network jump.
+const JUMP_GLITCH_BARS: u64 = 24;
+
+/// Renders the countdown timer, run seed, held keycards and, in endless mode, chunks reached
+/// in a rounded box and a dot crosshair. Static tiles and network jumps garble the timer and seed.
+#[allow(clippy::too_many_arguments)]
 pub fn render_hud(
     mut contexts: EguiContexts,
     timer: Res<GameTimer>,
     time: Res<Time>,
+    seed: Res<RunSeed>,
+    keyring: Res<Keyring>,
+    tile: Res<CurrentTile>,
+    jump_glitch: Res<JumpGlitch>,
+    endless: Option<Res<EndlessMaze>>,
 ) {
     let ctx = contexts.ctx_mut();
     let remaining = timer.remaining.ceil() as u32;
@@ -31,12 +51,53 @@ pub fn render_hud(
                 .inner_margin(egui::Margin::symmetric(12.0, 8.0))
                 .show(ui, |ui| {
                     ui.set_min_width(160.0);
+                    // Reshuffled twenty times a second
+                    let garbled = tile.0 == TileKind::Static || jump_glitch.strength() > 0.0;
+                    let noise = garbled.then(|| (time.elapsed_secs() * 20.0) as u64);
+                    let readout = |text: String| match noise {
+                        Some(noise) => scramble(&text, noise),
+                        None => text,
+                    };
                     ui.label(egui::RichText::new(
-                        format!("{:02}:{:02}", remaining, ms)
+                        readout(format!("{:02}:{:02}", remaining, ms))
                     ).monospace().size(48.0).color(color).strong());
+                    ui.label(egui::RichText::new(readout(format!("SEED {}", seed.0)))
+                        .monospace().size(14.0).color(egui::Color32::from_rgb(120, 120, 120)));
+                    if let Some(endless) = &endless {
+                        ui.label(egui::RichText::new(format!("CHUNKS {}", endless.reached.len()))
+                            .monospace().size(14.0).color(egui::Color32::from_rgb(120, 120, 120)));
+                    }
+                    if !keyring.held.is_empty() {
+                        ui.horizontal(|ui| {
+                            for color in &keyring.held {
+                                let [r, g, b] = color.rgb();
+                                ui.label(egui::RichText::new("■")
+                                    .size(18.0).color(egui::Color32::from_rgb(r, g, b)));
+                            }
+                        });
+                    }
                 });
         });
 
+    // Jump glitch — bars of noise across the screen, thinning out as it fades
+    let strength = jump_glitch.strength();
+    if strength > 0.0 {
+        let screen = ctx.screen_rect();
+        let frame = (time.elapsed_secs() * 30.0) as u64;
+        let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, "jump_glitch".into()));
+        for bar in 0..(JUMP_GLITCH_BARS as f32 * strength).ceil() as u64 {
+            let noise = splitmix64(frame ^ (bar << 32));
+            let y = screen.top() + (noise % 1000) as f32 / 1000.0 * screen.height();
+            let height = 2.0 + ((noise >> 10) % 24) as f32;
+            let shade = (noise >> 20) as u8;
+            painter.rect_filled(
+                egui::Rect::from_min_size(egui::pos2(screen.left(), y), egui::vec2(screen.width(), height)),
+                0.0,
+                egui::Color32::from_rgba_unmultiplied(shade / 4, shade, shade / 2, (200.0 * strength) as u8),
+            );
+        }
+    }
+
     // Crosshair — small white dot at screen center
     egui::Area::new("crosshair".into())
         .fixed_pos(egui::pos2(
@@ -54,4 +115,18 @@ pub fn render_hud(
                 egui::Color32::from_rgba_unmultiplied(255, 255, 255, 180),
             );
         });
-}
\ No newline at end of file
+}
+
+/// Replaces every visible character of `text` with a noise glyph picked from `noise`.
+fn scramble(text: &str, noise: u64) -> String {
+    text.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if c == ' ' {
+                return c;
+            }
+            let pick = splitmix64(noise ^ i as u64) as usize % STATIC_GLYPHS.len();
+            STATIC_GLYPHS[pick] as char
+        })
+        .collect()
+}